@@ -0,0 +1,354 @@
+//! Animated replays of solutions.
+//!
+//! A replay shows the initial board and the board after every move, one
+//! frame per `drench`, with a move counter (like `3/20`) below the board.
+//! Replays can be written as animated GIF or as animated PNG (APNG). The
+//! frames are drawn with the `render` module.
+
+use board::Board;
+use color::Color;
+use deflate;
+use error::Error;
+use gif::{self, SetParameter};
+use image::{self, Image, Rgb};
+use render::{self, RenderOptions};
+use std::borrow::Cow;
+use std::cmp;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::iter;
+use std::path::Path;
+
+
+/// Options for animated replays.
+#[derive(Clone, Debug)]
+pub struct AnimationOptions {
+    /// How every frame is drawn.
+    pub render: RenderOptions,
+    /// Time every frame is shown, in milliseconds.
+    pub delay: u32,
+    /// Show the move counter below the board.
+    pub caption: bool,
+}
+
+impl Default for AnimationOptions {
+    fn default() -> Self {
+        AnimationOptions {
+            render: RenderOptions {
+                highlight_owned: true,
+                .. RenderOptions::default()
+            },
+            delay: 500,
+            caption: true,
+        }
+    }
+}
+
+/// The maximum number of frames of a replay, i.e. one more than the number
+/// of moves. Every frame is also limited to `render::MAX_PIXELS` pixels.
+pub const MAX_FRAMES: usize = 10_000;
+
+/// Returns all frames of the replay: the initial board and the board after
+/// every move.
+pub fn replay_frames(b: &Board, moves: &[Color], options: &AnimationOptions)
+    -> Result<Vec<Image>, Error>
+{
+    frames(b, moves, options)?.collect()
+}
+
+/// Writes the replay as animated GIF. The frames are encoded one by one, so
+/// only one of them is kept in memory.
+///
+/// Errors of the writer are returned as `Error::Io` with the path `replay`.
+pub fn write_gif<W: Write>(
+    w: W,
+    b: &Board,
+    moves: &[Color],
+    options: &AnimationOptions,
+) -> Result<(), Error> {
+    let mut frames = frames(b, moves, options)?;
+    let first = frames.next().unwrap()?;
+    let (width, height) = (first.width(), first.height());
+    let max = ::std::u16::MAX as u32;
+    if width > max || height > max {
+        return Err(Error::InvalidSize(format!(
+            "the replay is too big for a GIF ({}x{}, but at most {}x{} \
+             pixels are allowed)",
+            width,
+            height,
+            max,
+            max,
+        )));
+    }
+
+    // All frames only use a few colors, so we can use one global palette
+    // instead of quantizing. Apart from the cells, the frames all contain
+    // the same colors, so the palette is made of the colors of the first
+    // frame and the colors of the board.
+    let mut palette: Vec<Rgb> = Vec::new();
+    let mut indices: HashMap<Rgb, u8> = HashMap::new();
+    let board_colors = (0..b.colors()).map(|c| image::to_rgb(Color::new(c)));
+    for p in first.pixels().iter().cloned().chain(board_colors) {
+        if indices.contains_key(&p) {
+            continue;
+        }
+        if palette.len() == 256 {
+            return Err(Error::InvalidSize(
+                "the replay uses more than 256 colors, which is the maximum \
+                 of a GIF".into()
+            ));
+        }
+        indices.insert(p, palette.len() as u8);
+        palette.push(p);
+    }
+
+    let flat: Vec<u8> = palette.iter()
+        .flat_map(|p| p.iter().cloned())
+        .collect();
+    let mut encoder = gif::Encoder::new(w, width as u16, height as u16, &flat)
+        .map_err(output_error)?;
+    encoder.set(gif::Repeat::Infinite).map_err(output_error)?;
+
+    // GIF delays are given in hundredths of a second
+    let delay = cmp::min(options.delay / 10, ::std::u16::MAX as u32) as u16;
+    for frame in iter::once(Ok(first)).chain(frames) {
+        let frame = frame?;
+        let mut buffer = Vec::with_capacity(frame.pixels().len());
+        for p in frame.pixels() {
+            match indices.get(p) {
+                Some(&i) => buffer.push(i),
+                None => {
+                    return Err(Error::InvalidSize(
+                        "the replay uses colors that are neither in the \
+                         first frame nor on the board".into()
+                    ));
+                }
+            }
+        }
+
+        let frame = gif::Frame {
+            width: width as u16,
+            height: height as u16,
+            delay: delay,
+            buffer: Cow::Owned(buffer),
+            .. gif::Frame::default()
+        };
+        encoder.write_frame(&frame).map_err(output_error)?;
+    }
+    Ok(())
+}
+
+/// Writes the replay as animated PNG (APNG). Viewers without APNG support
+/// show the initial board. Like `write_gif`, the frames are encoded one by
+/// one.
+///
+/// Errors of the writer are returned as `Error::Io` with the path `replay`.
+pub fn write_apng<W: Write>(
+    mut w: W,
+    b: &Board,
+    moves: &[Color],
+    options: &AnimationOptions,
+) -> Result<(), Error> {
+    let mut frames = frames(b, moves, options)?;
+    let first = frames.next().unwrap()?;
+    let (width, height) = (first.width(), first.height());
+    let delay = cmp::min(options.delay, ::std::u16::MAX as u32) as u16;
+
+    w.write_all(b"\x89PNG\r\n\x1a\n").map_err(output_error)?;
+
+    // 8 bit RGB, no interlacing
+    let mut ihdr = Vec::new();
+    push_u32(&mut ihdr, width);
+    push_u32(&mut ihdr, height);
+    ihdr.extend_from_slice(&[8, 2, 0, 0, 0]);
+    write_chunk(&mut w, b"IHDR", &ihdr).map_err(output_error)?;
+
+    // Number of frames and number of plays (0 = infinite)
+    let mut actl = Vec::new();
+    push_u32(&mut actl, moves.len() as u32 + 1);
+    push_u32(&mut actl, 0);
+    write_chunk(&mut w, b"acTL", &actl).map_err(output_error)?;
+
+    // Frame control and frame data chunks share one sequence number
+    let mut sequence = 0;
+    for (i, frame) in iter::once(Ok(first)).chain(frames).enumerate() {
+        let frame = frame?;
+        let mut fctl = Vec::new();
+        push_u32(&mut fctl, sequence);
+        push_u32(&mut fctl, width);
+        push_u32(&mut fctl, height);
+        push_u32(&mut fctl, 0);
+        push_u32(&mut fctl, 0);
+        // delay (as fraction of a second), dispose and blend operation
+        push_u16(&mut fctl, delay);
+        push_u16(&mut fctl, 1000);
+        fctl.extend_from_slice(&[0, 0]);
+        write_chunk(&mut w, b"fcTL", &fctl).map_err(output_error)?;
+        sequence += 1;
+
+        // Every row starts with the filter type (0 = none)
+        let mut raw = Vec::with_capacity(
+            (3 * width as usize + 1) * height as usize
+        );
+        for y in 0..height {
+            raw.push(0);
+            for x in 0..width {
+                raw.extend_from_slice(&frame.pixel(x, y));
+            }
+        }
+        let compressed = deflate::deflate_bytes_zlib(&raw);
+
+        // The first frame is the default image
+        if i == 0 {
+            write_chunk(&mut w, b"IDAT", &compressed)
+                .map_err(output_error)?;
+        } else {
+            let mut fdat = Vec::with_capacity(compressed.len() + 4);
+            push_u32(&mut fdat, sequence);
+            fdat.extend_from_slice(&compressed);
+            write_chunk(&mut w, b"fdAT", &fdat).map_err(output_error)?;
+            sequence += 1;
+        }
+    }
+
+    write_chunk(&mut w, b"IEND", &[]).map_err(output_error)?;
+    Ok(())
+}
+
+/// Writes the replay to the given file. The format is determined by the
+/// extension: `.gif` for GIF and `.png` or `.apng` for APNG.
+pub fn save_replay<P: AsRef<Path>>(
+    path: P,
+    b: &Board,
+    moves: &[Color],
+    options: &AnimationOptions,
+) -> Result<(), Error> {
+    let path = path.as_ref();
+    let name = path.display().to_string();
+    let ext = path.extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase());
+    let res = match ext.as_deref() {
+        Some("gif") => {
+            let f = File::create(path).map_err(|e| Error::io(&*name, e))?;
+            write_gif(BufWriter::new(f), b, moves, options)
+        }
+        Some("png") | Some("apng") => {
+            let f = File::create(path).map_err(|e| Error::io(&*name, e))?;
+            write_apng(BufWriter::new(f), b, moves, options)
+        }
+        _ => {
+            return Err(Error::io(name.clone(), format!(
+                "unknown animation format of '{}' (use '.gif', '.png' or \
+                 '.apng')",
+                name,
+            )));
+        }
+    };
+
+    // Replace the placeholder path of the writer errors
+    res.map_err(|e| match e {
+        Error::Io { msg, .. } => Error::Io { path: name, msg: msg },
+        e => e,
+    })
+}
+
+/// The frames of a replay, rendered one at a time.
+struct Frames<'a> {
+    board: Board,
+    moves: &'a [Color],
+    options: &'a AnimationOptions,
+    next: usize,
+}
+
+/// Returns the frames of the replay or an error if there are more than
+/// `MAX_FRAMES`.
+fn frames<'a>(b: &Board, moves: &'a [Color], options: &'a AnimationOptions)
+    -> Result<Frames<'a>, Error>
+{
+    if moves.len() + 1 > MAX_FRAMES {
+        return Err(Error::InvalidSize(format!(
+            "the replay would have {} frames, but at most {} are allowed",
+            moves.len() + 1,
+            MAX_FRAMES,
+        )));
+    }
+
+    Ok(Frames {
+        board: b.clone(),
+        moves: moves,
+        options: options,
+        next: 0,
+    })
+}
+
+impl<'a> Iterator for Frames<'a> {
+    type Item = Result<Image, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let i = self.next;
+        if i > self.moves.len() {
+            return None;
+        }
+        if i > 0 {
+            self.board.drench(self.moves[i - 1]);
+        }
+        self.next += 1;
+
+        let render = &self.options.render;
+        Some(if self.options.caption {
+            let caption = format!("{}/{}", i, self.moves.len());
+            render::render_captioned(&self.board, &caption, render)
+        } else {
+            render::render_board(&self.board, render)
+        })
+    }
+}
+
+/// Converts errors of the writer of `write_gif` and `write_apng`.
+fn output_error(e: io::Error) -> Error {
+    Error::io("replay", e)
+}
+
+fn push_u16(out: &mut Vec<u8>, v: u16) {
+    out.extend_from_slice(&[(v >> 8) as u8, v as u8]);
+}
+
+fn push_u32(out: &mut Vec<u8>, v: u32) {
+    push_u16(out, (v >> 16) as u16);
+    push_u16(out, v as u16);
+}
+
+/// Writes one PNG chunk: length, type, data and CRC.
+fn write_chunk<W: Write>(w: &mut W, kind: &[u8; 4], data: &[u8])
+    -> io::Result<()>
+{
+    let mut header = Vec::with_capacity(8);
+    push_u32(&mut header, data.len() as u32);
+    header.extend_from_slice(kind);
+    w.write_all(&header)?;
+    w.write_all(data)?;
+
+    let crc = crc32(kind.iter().chain(data));
+    let mut footer = Vec::with_capacity(4);
+    push_u32(&mut footer, crc);
+    w.write_all(&footer)?;
+    Ok(())
+}
+
+/// The CRC used by PNG (CRC-32 with polynomial `0xedb88320`).
+fn crc32<'a, I: Iterator<Item=&'a u8>>(bytes: I) -> u32 {
+    let mut crc = 0xffff_ffffu32;
+    for &b in bytes {
+        crc ^= b as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
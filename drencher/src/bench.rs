@@ -1,34 +1,52 @@
-use super::{gen_board, get_player};
-use solver::{Solver, Solution};
+use super::{gen_board, generator_seed, get_player};
+use error::Error;
+use solver::{CancelToken, FailureReason, SolveOutcome};
 use board::Board;
+use json::{self, GameRecord};
+use verify;
+use notation;
+use std::fs::File;
+use std::io::Write;
 use time::Duration;
 use term_painter::{ToStyle, Color};
 use rayon::prelude::*;
 use pbr::ProgressBar;
 use std::sync::Mutex;
-use std::sync::atomic::{AtomicUsize, Ordering};
 use std::f64;
 
 struct RunOutcome {
     board: Board,
     elapsed_time: Duration,
-    moves: Solution,
+    outcome: SolveOutcome,
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn run_benchmark(
     init_algo: &str,
-    size: u8,
+    width: u16,
+    height: u16,
+    colors: u8,
     player: &str,
     count: usize,
     progress: bool,
     threading: bool,
-) -> Result<(), ()> {
+    timeout: Option<Duration>,
+    record_path: Option<&str>,
+) -> Result<(), Error> {
     println!("Benchmarking player '{}' ({} iterations)", player, count);
 
-    let player = try!(get_player(player));
+    let player_name = player;
+    let player = get_player(player)?;
+
+    // Boards from files or with invalid parameters fail for every run, so
+    // we check this once up front.
+    gen_board(init_algo, width, height, colors, 0)?;
     let mut benchmark = Vec::with_capacity(count);
     let pb = Mutex::new(ProgressBar::new(count as u64));
-    let no_solution_count = AtomicUsize::new(0);
+
+    // Records of all games (with the index of the run), only collected if
+    // the records should be saved.
+    let records = Mutex::new(Vec::new());
 
     let weight = if threading { f64::INFINITY } else { 0f64 };
 
@@ -36,50 +54,61 @@ pub fn run_benchmark(
         (0..count).into_par_iter().weight(weight).map(|i| {
 
             // generate board and get player
-            let board = match gen_board(init_algo, size, i as u64) {
+            let id = i as u64;
+            let board = match gen_board(init_algo, width, height, colors, id) {
                 Ok(board) => board,
                 Err(_) => return None,
             };
 
-            // let the player try to solve the board
+            // let the player try to solve the board (within the time limit)
+            let cancel = timeout.map(CancelToken::with_timeout)
+                .unwrap_or_default();
             let mut res = None;
-            let mut run_outcome = RunOutcome {
-                board: board.clone(),
-                elapsed_time: Duration::zero(),
-                moves: Vec::new(),  // will be overridden later
-            };
-
-            // collect solve outcome
-            run_outcome.elapsed_time = Duration::span(|| {
-                res = Some(player.solve(board));
+            let elapsed_time = Duration::span(|| {
+                res = Some(player.solve_until(board.clone(), &cancel));
             });
+            let run_outcome = RunOutcome {
+                board: board,
+                elapsed_time: elapsed_time,
+                outcome: res.unwrap(),
+            };
 
             // increment progress bar
             if progress {
                 pb.lock().unwrap().inc();
             }
 
-            // if the solver didn't find a solution, we will increment the
-            // no_solution_count and ignore this run
-            run_outcome.moves = match res.unwrap() {
-                Ok(res) => res,
-                Err(_) => {
-                    // I don't understand memory orderings...
-                    no_solution_count.fetch_add(1, Ordering::SeqCst);
-                    return None;
-                }
-            };
+            if record_path.is_some() {
+                records.lock().unwrap().push((i, GameRecord {
+                    board: run_outcome.board.clone(),
+                    generator: init_algo.to_string(),
+                    seed: generator_seed(init_algo, id),
+                    player: player_name.to_string(),
+                    moves: run_outcome.outcome.moves.clone(),
+                    solved: run_outcome.outcome.is_solved(),
+                }));
+            }
 
             Some(run_outcome)
         }).collect_into(&mut benchmark);
     });
 
     // from now on: no multithreading anymore
-    let no_solution_count = no_solution_count.load(Ordering::SeqCst);
+    if let Some(path) = record_path {
+        let mut records = records.into_inner().unwrap();
+        records.sort_by_key(|&(i, _)| i);
+        let records: Vec<_> = records.into_iter().map(|(_, r)| r).collect();
+
+        let games = json::games_to_json(&records);
+        let res = File::create(path).and_then(|mut f| {
+            writeln!(f, "{}", json::to_pretty_string(&games))
+        });
+        res.map_err(|e| Error::io(path, e))?;
+    }
 
     // remove runs where some error occured
-    let benchmark: Vec<_> = benchmark.into_iter().filter_map(|e| e).collect();
-    if benchmark.len() < count - no_solution_count {
+    let benchmark: Vec<_> = benchmark.into_iter().flatten().collect();
+    if benchmark.len() < count {
         println!(
             "{} {} runs returned with an error!",
             Color::BrightYellow.paint("!!! Warning:"),
@@ -87,11 +116,28 @@ pub fn run_benchmark(
         );
     }
 
-    if no_solution_count > 0 {
+    // if the solver didn't find a solution, we will report why and ignore
+    // the run
+    let (benchmark, failed): (Vec<_>, Vec<_>) = benchmark.into_iter()
+        .partition(|run| run.outcome.is_solved());
+    if !failed.is_empty() {
+        let reasons = [
+            FailureReason::MoveLimit,
+            FailureReason::Aborted,
+            FailureReason::Unsupported,
+        ];
+        let reasons: Vec<_> = reasons.iter().filter_map(|&reason| {
+            let n = failed.iter()
+                .filter(|run| run.outcome.failure == Some(reason))
+                .count();
+            if n > 0 { Some(format!("{}: {}", reason, n)) } else { None }
+        }).collect();
+
         println!(
-            "{} {} runs did not find a solution!",
+            "{} {} runs did not find a solution ({})!",
             Color::BrightYellow.paint("!!! Warning:"),
-            no_solution_count
+            failed.len(),
+            reasons.join(", "),
         );
     }
 
@@ -100,12 +146,9 @@ pub fn run_benchmark(
     let count_before = benchmark.len();
     let benchmark: Vec<_> = benchmark.into_iter()
         .filter(|run| {
-            let mut board = run.board.clone();
-            for &c in &run.moves {
-                board.drench(c);
-            }
-            board.is_drenched()
-        }).collect();
+            verify::verify(&run.board, &run.outcome.moves).is_valid()
+        })
+        .collect();
 
     if count_before > benchmark.len() {
         println!(
@@ -116,12 +159,10 @@ pub fn run_benchmark(
     }
 
     // all runs failed -> return
-    if count_before - benchmark.len() == count {
-        println!(
-            "{} all runs returned a wrong result - aborting!",
-            Color::Red.paint("!!! Error:"),
-        );
-        return Ok(());
+    if benchmark.is_empty() {
+        return Err(Error::Solver(
+            "no run returned a valid solution - aborting".into()
+        ));
     }
 
     // calc output
@@ -131,9 +172,24 @@ pub fn run_benchmark(
     let min_run = benchmark.iter().min_by_key(|elem| elem.elapsed_time).unwrap();
     let max_run = benchmark.iter().max_by_key(|elem| elem.elapsed_time).unwrap();
     let num_moves = benchmark.iter().fold(0, |sum, elem|
-        sum + elem.moves.len()
+        sum + elem.outcome.moves.len()
     );
     let valid_count = benchmark.len();
+    let optimal_count = benchmark.iter()
+        .filter(|elem| elem.outcome.optimal)
+        .count();
+    let nodes_expanded = benchmark.iter().fold(0, |sum, elem|
+        sum + elem.outcome.nodes_expanded
+    );
+    let improvements: Vec<_> = benchmark.iter()
+        .filter_map(|elem| elem.outcome.initial_bound.map(|initial| {
+            initial.saturating_sub(elem.outcome.moves.len())
+        }))
+        .collect();
+    let peak_frontier = benchmark.iter()
+        .map(|elem| elem.outcome.peak_frontier)
+        .max()
+        .unwrap();
 
     // --- output of the results
     println!(
@@ -154,17 +210,49 @@ pub fn run_benchmark(
         Color::BrightYellow.paint(num_moves),
         Color::BrightBlue.paint((num_moves as f64) / (valid_count as f64)),
     );
+    if optimal_count > 0 {
+        println!(
+            "+++ Proven optimal: {} of {} runs",
+            Color::BrightYellow.paint(optimal_count),
+            valid_count,
+        );
+    }
+    if nodes_expanded > 0 {
+        println!(
+            "+++ Nodes expanded: {} ({} on average), peak frontier: {}",
+            Color::BrightYellow.paint(nodes_expanded),
+            Color::BrightBlue.paint(
+                (nodes_expanded as f64) / (valid_count as f64)
+            ),
+            Color::BrightBlue.paint(peak_frontier),
+        );
+    }
+    if !improvements.is_empty() {
+        let improved: usize = improvements.iter().sum();
+        println!(
+            "+++ Initial bound improved by: {} moves ({} on average, {} of \
+             {} runs improved)",
+            Color::BrightYellow.paint(improved),
+            Color::BrightBlue.paint(
+                (improved as f64) / (improvements.len() as f64)
+            ),
+            improvements.iter().filter(|&&n| n > 0).count(),
+            improvements.len(),
+        );
+    }
 
     println!(
         "Initial board that took the most time (solved with {} moves):\n{}",
-        Color::BrightBlue.paint(max_run.moves.len()),
+        Color::BrightBlue.paint(max_run.outcome.moves.len()),
         max_run.board,
     );
+    println!("Moves: {}\n", notation::format_moves(&max_run.outcome.moves));
     println!(
         "Initial board that took the least time (solved with {} moves):\n{}",
-        Color::BrightBlue.paint(min_run.moves.len()),
+        Color::BrightBlue.paint(min_run.outcome.moves.len()),
         min_run.board,
     );
+    println!("Moves: {}", notation::format_moves(&min_run.outcome.moves));
 
     Ok(())
 }
@@ -1,186 +1,304 @@
 #![allow(dead_code)]
 
-use color::Color;
+use color::{Color, MAX_COLORS};
 use std::ops;
 use std::fmt;
 use rand;
 use rand::distributions::{Range, IndependentSample};
 use rand::{IsaacRng, SeedableRng, Rng};
-use std::iter::repeat;
+use std::mem;
+
+/// A position on the board: `(x, y)` with `x` being the column and `y` the
+/// row. `(0, 0)` is the top left corner.
+pub type Pos = (u16, u16);
 
 
 #[derive(Clone)]
 pub struct Board {
-    size: u8,
+    width: u16,
+    height: u16,
+    colors: u8,
     cells: Vec<Color>,
+
+    // The field is the region connected to the top left corner, which is
+    // owned by the player. It's maintained incrementally by `drench` so that
+    // each move only costs about the number of newly captured cells. Note
+    // that the entries of `cells` of owned cells are outdated: the color of
+    // all owned cells is `field_color`.
+    field_color: Color,
+    owned: Vec<bool>,
+    field: Vec<Pos>,
+
+    // All cells that are adjacent to the field, but not part of it, sorted
+    // by their color. `in_border` is used to avoid duplicates.
+    border: Vec<Vec<Pos>>,
+    in_border: Vec<bool>,
 }
 
 
 impl Board {
-    pub fn uniform(size: u8) -> Board {
-        Board {
-            size: size,
-            cells: repeat(Color::new(0)).take((size as usize).pow(2)).collect()
-        }
+    /// Creates a board from the given cells (row by row, starting at the top
+    /// left corner). Panics if the number of cells doesn't match the
+    /// dimensions or a cell has a color not in `0..colors`.
+    pub fn from_cells(width: u16, height: u16, colors: u8, cells: Vec<Color>)
+        -> Board
+    {
+        check_colors(colors);
+        assert!(width > 0 && height > 0, "board must not be empty");
+        assert_eq!(
+            cells.len(),
+            width as usize * height as usize,
+            "number of cells doesn't match the dimensions {}x{}",
+            width,
+            height,
+        );
+        assert!(
+            cells.iter().all(|c| c.tag < colors),
+            "board contains a color which is not in 0..{}",
+            colors,
+        );
+
+        let len = cells.len();
+        let mut board = Board {
+            width: width,
+            height: height,
+            colors: colors,
+            field_color: cells[0],
+            cells: cells,
+            owned: vec![false; len],
+            field: Vec::new(),
+            border: vec![Vec::new(); colors as usize],
+            in_border: vec![false; len],
+        };
+        board.flood(vec![(0, 0)]);
+        board
+    }
+
+    pub fn uniform(width: u16, height: u16, colors: u8) -> Board {
+        let cells = vec![Color::new(0); width as usize * height as usize];
+        Self::from_cells(width, height, colors, cells)
+    }
+
+    /// Returns the number of columns of the board.
+    pub fn width(&self) -> u16 {
+        self.width
+    }
 
+    /// Returns the number of rows of the board.
+    pub fn height(&self) -> u16 {
+        self.height
     }
 
-    pub fn size(&self) -> u8 {
-        self.size
+    /// Returns the number of colors of this board. All cells have a color
+    /// with a tag in `0..colors`.
+    pub fn colors(&self) -> u8 {
+        self.colors
     }
 
-    pub fn random(size: u8) -> Board {
+    pub fn random(width: u16, height: u16, colors: u8) -> Board {
         let mut rng = rand::thread_rng();
-        Self::with_rng(size, &mut rng)
+        Self::with_rng(width, height, colors, &mut rng)
     }
 
-    /// Returns the nth permutation of a board with the given size. Note that
-    /// there are 6^(size^2) permutations (many!). The number of permutations
-    /// is greater than u64::MAX for size=5 already!
-    pub fn permutation(size: u8, mut n: u64) -> Board {
-        let mut cells = vec![Color::new(0); (size as usize).pow(2)];
+    /// Returns the nth permutation of a board with the given dimensions. Note
+    /// that there are colors^(width*height) permutations (many!). With 6
+    /// colors, the number of permutations is greater than u64::MAX for a 5x5
+    /// board already! To address all boards, use board IDs (see `to_id` and
+    /// `from_id`), which use the same order of boards.
+    pub fn permutation(width: u16, height: u16, colors: u8, mut n: u64)
+        -> Board
+    {
+        check_colors(colors);
+        let mut cells = vec![Color::new(0); width as usize * height as usize];
 
         for cell in &mut cells {
-            *cell = Color::new((n % 6) as u8);
-            n /= 6;
-        }
-        Board {
-            size: size,
-            cells: cells,
+            *cell = Color::new((n % colors as u64) as u8);
+            n /= colors as u64;
         }
+        Self::from_cells(width, height, colors, cells)
     }
 
-    pub fn deterministic_random(size: u8, id: u64) -> Board {
+    pub fn deterministic_random(width: u16, height: u16, colors: u8, id: u64)
+        -> Board
+    {
         let id = (id & ::std::u32::MAX as u64) as u32;
         let mut rng = IsaacRng::from_seed(&[id, id + 42, id + 27, id + 1337]);
-        Self::with_rng(size, &mut rng)
+        Self::with_rng(width, height, colors, &mut rng)
     }
 
-    fn with_rng(size: u8, mut rng: &mut Rng) -> Board {
+    fn with_rng(width: u16, height: u16, colors: u8, mut rng: &mut dyn Rng)
+        -> Board
+    {
+        check_colors(colors);
         let mut v = Vec::with_capacity(
-            (size as usize).pow(2)
+            width as usize * height as usize
         );
-        let range = Range::new(0, 6);
+        let range = Range::new(0, colors);
 
-        for _ in 0..(size as u16) * (size as u16) {
+        for _ in 0..(width as usize * height as usize) {
             let n = range.ind_sample(&mut rng);
             v.push(Color::new(n));
         }
 
-        Board {
-            size: size,
-            cells: v,
-        }
+        Self::from_cells(width, height, colors, v)
     }
 
-    pub fn drench(&mut self, new: Color) {
-        if new != self[(0, 0)] {
-            for (x, y) in self.field_coords().0 {
-                self[(x, y)] = new;
-            }
+    /// Sets the color of a single cell. As this can change the field in
+    /// arbitrary ways, the field is recalculated from scratch. Thus this is
+    /// an `O(width * height)` operation.
+    ///
+    /// This replaces the former `IndexMut` implementation: a `&mut Color`
+    /// would let callers change a cell without the board noticing, leaving
+    /// the field and its border outdated.
+    pub fn set(&mut self, pos: Pos, color: Color) {
+        assert!(color.tag < self.colors, "color not in 0..{}", self.colors);
+
+        // Write back the color of all owned cells, because we need the real
+        // colors of all cells to recalculate the field.
+        let idx = self.index_of(pos);
+        for &owned in &self.field {
+            let i = self.index_of(owned);
+            self.cells[i] = self.field_color;
         }
+        self.cells[idx] = color;
+
+        let cells = mem::take(&mut self.cells);
+        *self = Self::from_cells(self.width, self.height, self.colors, cells);
     }
 
-    pub fn field_coords(&self) -> (Vec<(u8, u8)>, Vec<(u8, u8)>) {
-        let mut stack = Vec::new();
-        let start_color = self[(0, 0)];
-        stack.push((0, 0));
+    pub fn drench(&mut self, new: Color) {
+        if new == self.field_color {
+            return;
+        }
 
-        let mut visited = Vec::new();
-        let mut border = Vec::new();
+        // All border cells with the new color are captured, as well as all
+        // cells connected to those with the same color.
+        self.field_color = new;
+        let captured = mem::take(&mut self.border[new.tag as usize]);
+        for &pos in &captured {
+            let i = self.index_of(pos);
+            self.in_border[i] = false;
+        }
+        self.flood(captured);
+    }
 
+    /// Adds all cells in `stack` and all cells connected to those with the
+    /// field color to the field. All cells in `stack` need to have the field
+    /// color. New adjacent cells are added to the border.
+    fn flood(&mut self, mut stack: Vec<Pos>) {
         while let Some((x, y)) = stack.pop() {
-            let already_visited = visited
-                .iter()
-                .find(|&&pos| pos == (x, y))
-                .is_none();
-            if already_visited {
-                if self[(x, y)] == start_color {
-                    visited.push((x, y));
-
-                    if x > 0 {
-                        stack.push((x - 1, y));
-                    }
-                    if y > 0 {
-                        stack.push((x, y - 1));
-                    }
-                    if x < self.size - 1 {
-                        stack.push((x + 1, y));
-                    }
-                    if y < self.size - 1 {
-                        stack.push((x, y + 1));
+            let i = self.index_of((x, y));
+            if self.owned[i] {
+                continue;
+            }
+            self.owned[i] = true;
+            self.field.push((x, y));
+
+            macro_rules! visit_neighbor {
+                ($pos:expr, $cond:expr) => {
+                    if $cond {
+                        let j = self.index_of($pos);
+                        let color = self.cells[j];
+                        if self.owned[j] {
+                            // already part of the field
+                        } else if color == self.field_color {
+                            stack.push($pos);
+                        } else if !self.in_border[j] {
+                            self.in_border[j] = true;
+                            self.border[color.tag as usize].push($pos);
+                        }
                     }
-                } else {
-                    border.push((x, y));
                 }
             }
+
+            visit_neighbor!((x - 1, y), x > 0);
+            visit_neighbor!((x + 1, y), x < self.width - 1);
+            visit_neighbor!((x, y - 1), y > 0);
+            visit_neighbor!((x, y + 1), y < self.height - 1);
         }
+    }
 
-        (visited, border)
+    /// Returns all cells of the field (owned by the player) and all cells
+    /// adjacent to the field.
+    pub fn field_coords(&self) -> (Vec<Pos>, Vec<Pos>) {
+        let border = self.border.iter()
+            .flat_map(|cells| cells.iter().cloned())
+            .collect();
+        (self.field.clone(), border)
     }
 
-    pub fn is_drenched(&self) -> bool {
-        self.cells.iter().all(|&c| c == self[(0, 0)])
+    /// Returns the number of cells owned by the player.
+    pub fn field_size(&self) -> usize {
+        self.field.len()
     }
 
-    pub fn adjacent_colors(&self) -> Vec<Color> {
-        let mut colors = [0; 6];
+    /// Returns all cells of the given color that are adjacent to the field.
+    /// These are the cells captured (together with the cells connected to
+    /// them) when drenching with that color.
+    pub fn border_cells(&self, color: Color) -> &[Pos] {
+        &self.border[color.tag as usize]
+    }
 
-        for (x, y) in self.field_coords().1 {
-            let color = self[(x, y)];
-            colors[color.tag as usize] += 1;
-        }
+    /// Returns true if the given cell is part of the field (owned by the
+    /// player).
+    pub fn is_owned(&self, pos: Pos) -> bool {
+        self.owned[self.index_of(pos)]
+    }
 
-        colors.iter().enumerate().filter_map(|(i, &count)| {
-            if count > 0 {
+    pub fn is_drenched(&self) -> bool {
+        self.field.len() == self.cells.len()
+    }
+
+    pub fn adjacent_colors(&self) -> Vec<Color> {
+        self.border.iter().enumerate().filter_map(|(i, cells)| {
+            if !cells.is_empty() {
                 Some(Color::new(i as u8))
             } else {
                 None
             }
         }).collect()
     }
-}
 
-impl ops::Index<(u8, u8)> for Board {
-    type Output = Color;
-    fn index(&self, (x, y): (u8, u8)) -> &Self::Output {
-        if x > self.size || y > self.size {
+    fn index_of(&self, (x, y): Pos) -> usize {
+        if x >= self.width || y >= self.height {
             panic!(
-                "x ({}) or y ({}) greater than size ({})",
-                x, y, self.size
+                "x ({}) or y ({}) out of bounds (width {}, height {})",
+                x, y, self.width, self.height
             );
         }
 
-        &self.cells[
-            (y as usize) * (self.size as usize)
-                + (x as usize)
-        ]
+        (y as usize) * (self.width as usize) + (x as usize)
     }
 }
 
-impl ops::IndexMut<(u8, u8)> for Board {
-    fn index_mut(&mut self, (x, y): (u8, u8)) -> &mut Self::Output {
-        if x > self.size || y > self.size {
-            panic!(
-                "x ({}) or y ({}) greater than size ({})",
-                x, y, self.size
-            );
-        }
+fn check_colors(colors: u8) {
+    if colors == 0 || colors > MAX_COLORS {
+        panic!(
+            "number of colors ({}) has to be between 1 and {}",
+            colors, MAX_COLORS
+        );
+    }
+}
 
-        &mut self.cells[
-            (y as usize) * (self.size as usize)
-                + (x as usize)
-        ]
+impl ops::Index<Pos> for Board {
+    type Output = Color;
+    fn index(&self, pos: Pos) -> &Self::Output {
+        let idx = self.index_of(pos);
+        if self.owned[idx] {
+            &self.field_color
+        } else {
+            &self.cells[idx]
+        }
     }
 }
 
 impl fmt::Display for Board {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        for y in 0..self.size {
-            for x in 0..self.size {
-                try!(self[(x, y)].fmt(f));
+        for y in 0..self.height {
+            for x in 0..self.width {
+                self[(x, y)].fmt(f)?;
             }
-            try!("\n".fmt(f));
+            "\n".fmt(f)?;
         }
         Ok(())
     }
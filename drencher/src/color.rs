@@ -1,5 +1,11 @@
 use std::fmt;
 
+/// The number of colors of a standard drench game.
+pub const DEFAULT_COLORS: u8 = 6;
+
+/// The maximum number of colors a board can have. Only for that many colors
+/// there are distinct RGB and terminal representations.
+pub const MAX_COLORS: u8 = 16;
 
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
 pub struct Color {
@@ -27,13 +33,23 @@ impl Color {
             3 => 0x2980b9,
             4 => 0x8e44ad,
             5 => 0x1abc9c,
+            6 => 0xe67e22,
+            7 => 0xecf0f1,
+            8 => 0x7f8c8d,
+            9 => 0xff7ab6,
+            10 => 0x2ecc71,
+            11 => 0x6c3483,
+            12 => 0x85c1e9,
+            13 => 0x935116,
+            14 => 0xf9e79f,
+            15 => 0x1b2631,
             _ => 0x000000,
         };
 
         [
             (((c & 0xFF0000) >> 16) as f32) / 255.0,
             (((c & 0x00FF00) >>  8) as f32) / 255.0,
-            (((c & 0x0000FF) >>  0) as f32) / 255.0,
+            ( (c & 0x0000FF)        as f32) / 255.0,
         ]
     }
 }
@@ -50,6 +66,16 @@ impl fmt::Display for Color {
             3 => Blue,
             4 => Magenta,
             5 => Cyan,
+            6 => BrightRed,
+            7 => White,
+            8 => BrightBlack,
+            9 => BrightMagenta,
+            10 => BrightGreen,
+            11 => BrightBlue,
+            12 => BrightCyan,
+            13 => BrightYellow,
+            14 => BrightWhite,
+            15 => Black,
             _ => White,
         }).paint("  ").fmt(f)
     }
@@ -0,0 +1,97 @@
+//! The error type of this crate.
+
+use std::error;
+use std::fmt;
+
+
+/// Everything that can go wrong when creating boards, choosing players or
+/// running games.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// There is no player with the given name.
+    UnknownPlayer(String),
+    /// The player exists, but is not available in this build (e.g. `human`
+    /// without the `gui` feature).
+    PlayerUnavailable(String),
+    /// There is no initial board algorithm with the given name.
+    UnknownGenerator(String),
+    /// The requested board dimensions or number of colors are out of range.
+    InvalidSize(String),
+    /// Some input (a board file, ID, image, solution, ...) couldn't be
+    /// parsed. `source` describes the input.
+    Parse {
+        source: String,
+        msg: String,
+    },
+    /// Reading or writing the given file failed.
+    Io {
+        path: String,
+        msg: String,
+    },
+    /// A solver failed to solve a board or returned a wrong result.
+    Solver(String),
+}
+
+impl Error {
+    /// Creates a `Parse` error.
+    pub fn parse<S: Into<String>, E: fmt::Display>(source: S, e: E) -> Self {
+        Error::Parse {
+            source: source.into(),
+            msg: e.to_string(),
+        }
+    }
+
+    /// Creates an `Io` error.
+    pub fn io<S: Into<String>, E: fmt::Display>(path: S, e: E) -> Self {
+        Error::Io {
+            path: path.into(),
+            msg: e.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::UnknownPlayer(ref name) => {
+                write!(f, "player '{}' does not exist", name)
+            }
+            Error::PlayerUnavailable(ref name) if name == "human" => {
+                write!(
+                    f,
+                    "player 'human' is not available: drencher was compiled \
+                     without the 'gui' feature. Use the player 'terminal' \
+                     instead",
+                )
+            }
+            Error::PlayerUnavailable(ref name) => {
+                write!(f, "player '{}' is not available", name)
+            }
+            Error::UnknownGenerator(ref name) => {
+                write!(f, "initial board algorithm '{}' doesn't exist", name)
+            }
+            Error::InvalidSize(ref msg) => msg.fmt(f),
+            Error::Parse { ref source, ref msg } => {
+                write!(f, "invalid {}: {}", source, msg)
+            }
+            Error::Io { ref path, ref msg } => {
+                write!(f, "couldn't access '{}': {}", path, msg)
+            }
+            Error::Solver(ref msg) => write!(f, "solver failed: {}", msg),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn description(&self) -> &str {
+        match *self {
+            Error::UnknownPlayer(_) => "unknown player",
+            Error::PlayerUnavailable(_) => "player not available",
+            Error::UnknownGenerator(_) => "unknown initial board algorithm",
+            Error::InvalidSize(_) => "invalid board size",
+            Error::Parse { .. } => "parse error",
+            Error::Io { .. } => "IO error",
+            Error::Solver(_) => "solver failed",
+        }
+    }
+}
@@ -0,0 +1,296 @@
+//! Region graph of a board.
+//!
+//! Every island of connected cells with the same color is represented by one
+//! node (a *region*) in an undirected graph. Two regions are connected if
+//! they touch each other on the board. Many questions about a board are a
+//! lot easier (and faster) to answer on this graph than cell by cell.
+//!
+//! The region containing the top left cell is called the *root*. Drenching
+//! the board corresponds to recoloring the root and merging it with all
+//! adjacent regions of the new color (see `RegionGraph::drench`).
+
+use board::{Board, Pos};
+use color::Color;
+use util::CellMap;
+use std::cmp;
+use std::collections::VecDeque;
+use std::fmt;
+use std::mem;
+
+/// Index of a region in the graph.
+pub type RegionId = u32;
+
+/// The id of the region containing the top left cell.
+pub const ROOT: RegionId = 0;
+
+/// One node of the region graph.
+#[derive(Clone, Debug)]
+struct Region {
+    color: Color,
+    cells: Vec<Pos>,
+    // sorted ids of all adjacent regions
+    neighbors: Vec<RegionId>,
+    // false if this region was merged into another one
+    alive: bool,
+}
+
+/// Undirected graph of all same-colored islands of a board.
+///
+/// Ids of regions are stable: merging two regions keeps the id of the first
+/// one, the second id becomes invalid. A freshly built graph uses the ids
+/// `0..len()`, with the root being `ROOT`.
+#[derive(Clone)]
+pub struct RegionGraph {
+    regions: Vec<Region>,
+    region_of: CellMap<RegionId>,
+    alive_count: usize,
+    colors: u8,
+}
+
+impl RegionGraph {
+    /// Builds the region graph of the given board.
+    pub fn new(b: &Board) -> Self {
+        // For every cell we store the region it belongs to, if we already
+        // visited it.
+        let mut map: CellMap<Option<RegionId>> =
+            CellMap::new(b.width(), b.height(), None);
+        let mut regions: Vec<Region> = Vec::new();
+        let (width, height) = (b.width(), b.height());
+
+        // We iterate row by row, so the top left cell is in the first region.
+        for y in 0..height {
+            for x in 0..width {
+                if map[(x, y)].is_some() {
+                    continue;
+                }
+
+                let id = regions.len() as RegionId;
+                let color = b[(x, y)];
+                let mut cells = Vec::new();
+                let mut neighbors = Vec::new();
+
+                // Flood fill the island. Adjacent cells which already belong
+                // to a region give us an edge; all other adjacent cells will
+                // add the edge to us once their region is created.
+                let mut stack = vec![(x, y)];
+                map[(x, y)] = Some(id);
+                while let Some((x, y)) = stack.pop() {
+                    cells.push((x, y));
+
+                    macro_rules! visit_neighbor {
+                        ($pos:expr, $cond:expr) => {
+                            if $cond {
+                                match map[$pos] {
+                                    None if b[$pos] == color => {
+                                        map[$pos] = Some(id);
+                                        stack.push($pos);
+                                    }
+                                    Some(other) if other != id => {
+                                        neighbors.push(other);
+                                    }
+                                    _ => {}
+                                }
+                            }
+                        }
+                    }
+
+                    visit_neighbor!((x - 1, y), x > 0);
+                    visit_neighbor!((x + 1, y), x < width - 1);
+                    visit_neighbor!((x, y - 1), y > 0);
+                    visit_neighbor!((x, y + 1), y < height - 1);
+                }
+
+                neighbors.sort();
+                neighbors.dedup();
+                for &other in &neighbors {
+                    // Ids are increasing, so it stays sorted.
+                    regions[other as usize].neighbors.push(id);
+                }
+
+                regions.push(Region {
+                    color: color,
+                    cells: cells,
+                    neighbors: neighbors,
+                    alive: true,
+                });
+            }
+        }
+
+        // Now every cell belongs to a region
+        let mut region_of = CellMap::new(width, height, ROOT);
+        for (id, region) in regions.iter().enumerate() {
+            for &pos in &region.cells {
+                region_of[pos] = id as RegionId;
+            }
+        }
+
+        RegionGraph {
+            alive_count: regions.len(),
+            regions: regions,
+            region_of: region_of,
+            colors: b.colors(),
+        }
+    }
+
+    /// Returns the number of regions in the graph (not counting regions that
+    /// were merged into others).
+    pub fn len(&self) -> usize {
+        self.alive_count
+    }
+
+    /// Returns true if the graph has no regions, which never happens for
+    /// graphs of a board.
+    pub fn is_empty(&self) -> bool {
+        self.alive_count == 0
+    }
+
+    /// Returns the number of colors of the underlying board.
+    pub fn colors(&self) -> u8 {
+        self.colors
+    }
+
+    /// Returns an iterator over the ids of all regions in the graph.
+    pub fn regions<'a>(&'a self) -> Box<dyn Iterator<Item=RegionId> + 'a> {
+        Box::new(
+            self.regions.iter()
+                .enumerate()
+                .filter(|&(_, r)| r.alive)
+                .map(|(id, _)| id as RegionId)
+        )
+    }
+
+    /// Returns true if the given id refers to a region of this graph.
+    pub fn contains(&self, id: RegionId) -> bool {
+        self.regions.get(id as usize).map(|r| r.alive).unwrap_or(false)
+    }
+
+    pub fn color(&self, id: RegionId) -> Color {
+        self.region(id).color
+    }
+
+    /// Returns the ids of all adjacent regions in ascending order.
+    pub fn neighbors(&self, id: RegionId) -> &[RegionId] {
+        &self.region(id).neighbors
+    }
+
+    /// Returns the number of cells of the given region.
+    pub fn size(&self, id: RegionId) -> usize {
+        self.region(id).cells.len()
+    }
+
+    /// Returns all cells of the given region.
+    pub fn cells(&self, id: RegionId) -> &[Pos] {
+        &self.region(id).cells
+    }
+
+    /// Returns the region the given cell belongs to.
+    pub fn region_of(&self, pos: Pos) -> RegionId {
+        self.region_of[pos]
+    }
+
+    /// Returns true if the whole board consists of one region.
+    pub fn is_drenched(&self) -> bool {
+        self.alive_count == 1
+    }
+
+    /// Returns the maximum distance (number of edges on a shortest path)
+    /// from the given region to any other region. Every move merges the root
+    /// with its direct neighbors only, so the eccentricity of the root is a
+    /// lower bound for the number of moves needed to drench the board.
+    pub fn eccentricity(&self, id: RegionId) -> usize {
+        let mut dist: Vec<Option<usize>> = vec![None; self.regions.len()];
+        let mut queue = VecDeque::new();
+        dist[id as usize] = Some(0);
+        queue.push_back(id);
+
+        let mut max = 0;
+        while let Some(current) = queue.pop_front() {
+            let d = dist[current as usize].unwrap();
+            max = cmp::max(max, d);
+            for &n in self.neighbors(current) {
+                if dist[n as usize].is_none() {
+                    dist[n as usize] = Some(d + 1);
+                    queue.push_back(n);
+                }
+            }
+        }
+        max
+    }
+
+    /// Merges region `b` into region `a`. Afterwards `a` contains all cells
+    /// of both regions and is adjacent to all neighbors of both regions. The
+    /// color of `a` is kept; the id `b` becomes invalid.
+    pub fn merge(&mut self, a: RegionId, b: RegionId) {
+        assert!(a != b, "can't merge a region with itself");
+        assert!(self.contains(a) && self.contains(b), "invalid region id");
+
+        let removed = mem::replace(&mut self.regions[b as usize], Region {
+            color: Color::new(0),
+            cells: Vec::new(),
+            neighbors: Vec::new(),
+            alive: false,
+        });
+        self.alive_count -= 1;
+
+        // Redirect all edges to `b` to `a`
+        for &n in &removed.neighbors {
+            let neighbors = &mut self.regions[n as usize].neighbors;
+            neighbors.retain(|&id| id != b);
+            if n != a {
+                if let Err(pos) = neighbors.binary_search(&a) {
+                    neighbors.insert(pos, a);
+                }
+            }
+        }
+
+        for &pos in &removed.cells {
+            self.region_of[pos] = a;
+        }
+
+        let region = &mut self.regions[a as usize];
+        region.cells.extend(removed.cells);
+        for n in removed.neighbors {
+            if n != a {
+                if let Err(pos) = region.neighbors.binary_search(&n) {
+                    region.neighbors.insert(pos, n);
+                }
+            }
+        }
+    }
+
+    /// Performs a move: the root gets the given color and is merged with all
+    /// adjacent regions of that color. Returns the number of regions merged
+    /// into the root.
+    pub fn drench(&mut self, color: Color) -> usize {
+        self.regions[ROOT as usize].color = color;
+
+        let captured: Vec<_> = self.neighbors(ROOT)
+            .iter()
+            .cloned()
+            .filter(|&n| self.color(n) == color)
+            .collect();
+        for &n in &captured {
+            self.merge(ROOT, n);
+        }
+
+        captured.len()
+    }
+
+    fn region(&self, id: RegionId) -> &Region {
+        let region = &self.regions[id as usize];
+        assert!(region.alive, "region {} was merged into another one", id);
+        region
+    }
+}
+
+// Custom Debug implementation for debugging purposes
+impl fmt::Debug for RegionGraph {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "RegionGraph ({} regions) ", self.len())?;
+        f.debug_map()
+            .entries(self.regions().map(|id| {
+                (id, (self.color(id), self.neighbors(id)))
+            }))
+            .finish()
+    }
+}
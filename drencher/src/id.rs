@@ -0,0 +1,276 @@
+//! Compact and shareable board IDs.
+//!
+//! A board ID looks like `4x3c6-1bWgJi` and consists of a header with the
+//! dimensions and the number of colors, followed by the cells encoded as one
+//! big number. The cells are interpreted as digits of a number in base
+//! `colors`, with the top left cell being the least significant digit (this
+//! is the same order as in `Board::permutation`). That number is written in
+//! base 64 with the digits `0-9`, `A-Z`, `a-z`, `-` and `_` and padded with
+//! leading zeros to the length needed for the biggest board with the given
+//! header.
+//!
+//! The encoding is a bijection: every board has exactly one ID and every
+//! valid ID describes exactly one board.
+
+use board::Board;
+use color::{Color, MAX_COLORS};
+use std::cmp;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+
+const ALPHABET: &[u8] =
+    b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz-_";
+
+/// Error returned when parsing an invalid board ID.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseIdError {
+    pub msg: String,
+}
+
+impl fmt::Display for ParseIdError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.msg.fmt(f)
+    }
+}
+
+impl Error for ParseIdError {
+    fn description(&self) -> &str {
+        &self.msg
+    }
+}
+
+impl Board {
+    /// Returns the ID of this board. See the module documentation for more
+    /// information.
+    pub fn to_id(&self) -> String {
+        // The cells as digits, most significant (bottom right) first
+        let mut digits = Vec::with_capacity(
+            self.width() as usize * self.height() as usize
+        );
+        for y in (0..self.height()).rev() {
+            for x in (0..self.width()).rev() {
+                digits.push(self[(x, y)].tag);
+            }
+        }
+
+        // Horner's method, with as many digits at once as fit into a limb
+        let colors = self.colors() as u32;
+        let mut number = BigNum::new();
+        for chunk in digits.chunks(chunk_len(colors)) {
+            let mut factor = 1;
+            let mut value = 0;
+            for &d in chunk {
+                factor *= colors;
+                value = value * colors + d as u32;
+            }
+            number.mul_add(factor, value);
+        }
+
+        // Every base 64 digit consists of 6 bits of the number
+        let len = payload_len(digits.len(), self.colors());
+        let payload: String = (0..len).rev()
+            .map(|i| ALPHABET[number.bits(6 * i, 6) as usize] as char)
+            .collect();
+        format!(
+            "{}x{}c{}-{}",
+            self.width(),
+            self.height(),
+            self.colors(),
+            payload,
+        )
+    }
+
+    /// Creates the board with the given ID. See the module documentation for
+    /// more information.
+    pub fn from_id(id: &str) -> Result<Board, ParseIdError> {
+        macro_rules! bail {
+            ($($arg:tt)*) => {
+                return Err(ParseIdError { msg: format!($($arg)*) })
+            }
+        }
+
+        // Split header and payload
+        let (header, payload) = match id.find('-') {
+            Some(pos) => (&id[..pos], &id[pos + 1..]),
+            None => bail!("board ID '{}' doesn't contain a '-'", id),
+        };
+        let (dims, colors) = match header.find('c') {
+            Some(pos) => (&header[..pos], &header[pos + 1..]),
+            None => bail!("header '{}' doesn't contain the colors", header),
+        };
+        let (width, height) = match dims.find('x') {
+            Some(pos) => (&dims[..pos], &dims[pos + 1..]),
+            None => bail!("header '{}' doesn't contain the dimensions", header),
+        };
+
+        let width = match parse_decimal::<u16>(width) {
+            Some(w) if w > 0 => w,
+            _ => bail!("invalid width '{}' in board ID", width),
+        };
+        let height = match parse_decimal::<u16>(height) {
+            Some(h) if h > 0 => h,
+            _ => bail!("invalid height '{}' in board ID", height),
+        };
+        let colors = match parse_decimal::<u8>(colors) {
+            Some(c) if c > 0 && c <= MAX_COLORS => c,
+            _ => bail!("invalid number of colors '{}' in board ID", colors),
+        };
+
+        // Decode the payload digits
+        let mut digits = Vec::with_capacity(payload.len());
+        for c in payload.bytes() {
+            match ALPHABET.iter().position(|&a| a == c) {
+                Some(d) => digits.push(d as u8),
+                None => bail!("invalid character '{}' in board ID", c as char),
+            }
+        }
+
+        let num_cells = width as usize * height as usize;
+        let len = payload_len(num_cells, colors);
+        if digits.len() != len {
+            bail!(
+                "the board ID for a {}x{} board with {} colors needs {} \
+                 characters after the '-', but has {}",
+                width,
+                height,
+                colors,
+                len,
+                digits.len(),
+            );
+        }
+
+        let mut number = BigNum::new();
+        for (i, &d) in digits.iter().rev().enumerate() {
+            number.set_bits(6 * i, d as u32);
+        }
+
+        // Split the number into the cells, as many at once as fit into a
+        // limb. The least significant digit is the top left cell. With only
+        // one color, the only valid number is 0.
+        let mut tags = Vec::with_capacity(num_cells);
+        if colors > 1 {
+            let len = chunk_len(colors as u32);
+            let divisor = (colors as u32).pow(len as u32);
+            while !number.is_zero() {
+                let mut value = number.div_rem(divisor);
+                for _ in 0..len {
+                    tags.push((value % colors as u32) as u8);
+                    value /= colors as u32;
+                }
+            }
+        }
+        let too_big = tags.iter().skip(num_cells).any(|&t| t != 0);
+        if too_big || !number.is_zero() {
+            bail!("number in board ID is too big for the board");
+        }
+
+        let mut cells: Vec<_> = tags.iter().map(|&t| Color::new(t)).collect();
+        cells.resize(num_cells, Color::new(0));
+
+        Ok(Board::from_cells(width, height, colors, cells))
+    }
+}
+
+/// Parses a decimal number without sign and leading zeros, so that every
+/// number has exactly one representation.
+fn parse_decimal<T: FromStr>(s: &str) -> Option<T> {
+    if s.starts_with('0') || !s.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    s.parse().ok()
+}
+
+/// Returns the number of base 64 digits needed to represent the biggest
+/// number with `num_cells` digits in base `colors`, which is
+/// `ceil(num_cells * log2(colors) / 6)`, but at least one.
+fn payload_len(num_cells: usize, colors: u8) -> usize {
+    let len = if colors.is_power_of_two() {
+        let bits = num_cells * colors.trailing_zeros() as usize;
+        bits.div_ceil(6)
+    } else {
+        // `colors^num_cells` is no power of 64, so the exact result is never
+        // an integer. The rounding error of `f64` (about 1e-16 relative)
+        // could only push it across one for boards with billions of cells.
+        let bits = num_cells as f64 * (colors as f64).log2();
+        (bits / 6.0).ceil() as usize
+    };
+    cmp::max(len, 1)
+}
+
+/// Returns the number of digits in base `base` which fit into one limb of a
+/// `BigNum`.
+fn chunk_len(base: u32) -> usize {
+    let mut len = 0;
+    let mut max = 1u64;
+    while len < 32 && max * base as u64 <= u32::max_value() as u64 {
+        max *= base as u64;
+        len += 1;
+    }
+    len
+}
+
+/// An arbitrarily big unsigned number, made of 32 bit limbs with the least
+/// significant limb first. Only the few operations needed to convert the
+/// cells from and to base 64 are implemented; each of them is linear in the
+/// length of the number.
+struct BigNum {
+    limbs: Vec<u32>,
+}
+
+impl BigNum {
+    fn new() -> Self {
+        BigNum { limbs: Vec::new() }
+    }
+
+    fn is_zero(&self) -> bool {
+        self.limbs.iter().all(|&l| l == 0)
+    }
+
+    /// Sets `self = self * factor + summand`.
+    fn mul_add(&mut self, factor: u32, summand: u32) {
+        let mut carry = summand as u64;
+        for limb in &mut self.limbs {
+            let v = *limb as u64 * factor as u64 + carry;
+            *limb = v as u32;
+            carry = v >> 32;
+        }
+        if carry > 0 {
+            self.limbs.push(carry as u32);
+        }
+    }
+
+    /// Sets `self = self / divisor` and returns the remainder.
+    fn div_rem(&mut self, divisor: u32) -> u32 {
+        let mut rem = 0u64;
+        for limb in self.limbs.iter_mut().rev() {
+            let v = (rem << 32) | *limb as u64;
+            *limb = (v / divisor as u64) as u32;
+            rem = v % divisor as u64;
+        }
+        while self.limbs.last() == Some(&0) {
+            self.limbs.pop();
+        }
+        rem as u32
+    }
+
+    /// Returns the `count` (at most 32) bits starting at bit `pos`.
+    fn bits(&self, pos: usize, count: usize) -> u32 {
+        let limb = |i| self.limbs.get(i).cloned().unwrap_or(0) as u64;
+        let both = limb(pos / 32) | limb(pos / 32 + 1) << 32;
+        ((both >> (pos % 32)) & ((1 << count) - 1)) as u32
+    }
+
+    /// Sets the bits starting at bit `pos` to the bits of `value`. These bits
+    /// have to be zero before.
+    fn set_bits(&mut self, pos: usize, value: u32) {
+        let both = (value as u64) << (pos % 32);
+        let i = pos / 32;
+        if self.limbs.len() < i + 2 {
+            self.limbs.resize(i + 2, 0);
+        }
+        self.limbs[i] |= both as u32;
+        self.limbs[i + 1] |= (both >> 32) as u32;
+    }
+}
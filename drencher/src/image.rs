@@ -0,0 +1,474 @@
+//! Reading and writing images and importing boards from them.
+//!
+//! Screenshots of drench puzzles can be turned into boards: the image (or a
+//! part of it) is divided into a grid of cells and every cell is mapped to
+//! the nearest color of a palette. By default the palette is made of the
+//! colors from `Color::as_rgb`, but custom palettes (e.g. the colors of the
+//! original Flash game) can be used, too. Cells whose color is not clearly
+//! closer to one palette entry than to all others are reported as ambiguous.
+//!
+//! Supported image formats are PNG and PPM (binary `P6` and ASCII `P3`).
+//! Images can be written as PNG and binary PPM; see the `render` module for
+//! drawing boards.
+
+use board::{Board, Pos};
+use color::{Color, MAX_COLORS};
+use png::{self, HasParameters};
+use std::cmp;
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufReader, Read, Write};
+use std::path::Path;
+
+
+/// An RGB color with 8 bits per channel.
+pub type Rgb = [u8; 3];
+
+/// Error returned when an image can't be read or imported.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ImageError {
+    pub msg: String,
+}
+
+impl ImageError {
+    fn new<S: Into<String>>(msg: S) -> Self {
+        ImageError {
+            msg: msg.into(),
+        }
+    }
+}
+
+impl fmt::Display for ImageError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.msg.fmt(f)
+    }
+}
+
+impl Error for ImageError {
+    fn description(&self) -> &str {
+        &self.msg
+    }
+}
+
+impl From<io::Error> for ImageError {
+    fn from(e: io::Error) -> Self {
+        ImageError::new(format!("IO error: {}", e))
+    }
+}
+
+/// A decoded RGB image.
+#[derive(Clone, Debug)]
+pub struct Image {
+    width: u32,
+    height: u32,
+    pixels: Vec<Rgb>,
+}
+
+impl Image {
+    /// Creates an image from the given pixels (row by row).
+    pub fn new(width: u32, height: u32, pixels: Vec<Rgb>) -> Self {
+        assert_eq!(pixels.len(), width as usize * height as usize);
+        Image {
+            width: width,
+            height: height,
+            pixels: pixels,
+        }
+    }
+
+    /// Reads a PNG or PPM image from the given file. The format is
+    /// determined by the content of the file, not the extension.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, ImageError> {
+        let mut data = Vec::new();
+        File::open(path).and_then(|f| {
+            BufReader::new(f).read_to_end(&mut data)
+        })?;
+
+        if data.starts_with(b"\x89PNG") {
+            Self::read_png(&data[..])
+        } else if data.starts_with(b"P6") || data.starts_with(b"P3") {
+            Self::read_ppm(&data[..])
+        } else {
+            Err(ImageError::new(
+                "unknown image format (only PNG and PPM are supported)"
+            ))
+        }
+    }
+
+    /// Decodes a PNG image.
+    pub fn read_png<R: Read>(r: R) -> Result<Self, ImageError> {
+        let mut decoder = png::Decoder::new(r);
+
+        // We want 8 bit per channel and no palette
+        decoder.set(png::TRANSFORM_EXPAND | png::TRANSFORM_STRIP_16);
+        let (info, mut reader) = decoder.read_info().map_err(|e| {
+            ImageError::new(format!("invalid PNG image: {}", e))
+        })?;
+        let mut buf = vec![0; info.buffer_size()];
+        reader.next_frame(&mut buf).map_err(|e| {
+            ImageError::new(format!("invalid PNG image: {}", e))
+        })?;
+
+        let (color_type, _) = reader.output_color_type();
+        let channels = match color_type {
+            png::ColorType::Grayscale => 1,
+            png::ColorType::GrayscaleAlpha => 2,
+            png::ColorType::RGB => 3,
+            png::ColorType::RGBA => 4,
+            png::ColorType::Indexed => {
+                return Err(
+                    ImageError::new("indexed PNG images weren't expanded")
+                );
+            }
+        };
+
+        let pixels = buf.chunks(channels)
+            .take(info.width as usize * info.height as usize)
+            .map(|p| match channels {
+                1 | 2 => [p[0], p[0], p[0]],
+                _ => [p[0], p[1], p[2]],
+            })
+            .collect();
+        Ok(Self::new(info.width, info.height, pixels))
+    }
+
+    /// Decodes a PPM image (binary `P6` or ASCII `P3`).
+    pub fn read_ppm<R: Read>(mut r: R) -> Result<Self, ImageError> {
+        let mut data = Vec::new();
+        r.read_to_end(&mut data)?;
+        let mut pos = 0;
+
+        // Returns the next whitespace separated token, skipping comments
+        let next_token = |pos: &mut usize| -> Option<String> {
+            loop {
+                while *pos < data.len() && is_space(data[*pos]) {
+                    *pos += 1;
+                }
+                if *pos < data.len() && data[*pos] == b'#' {
+                    while *pos < data.len() && data[*pos] != b'\n' {
+                        *pos += 1;
+                    }
+                } else {
+                    break;
+                }
+            }
+
+            let start = *pos;
+            while *pos < data.len() && !is_space(data[*pos]) {
+                *pos += 1;
+            }
+            if start == *pos {
+                None
+            } else {
+                Some(String::from_utf8_lossy(&data[start..*pos]).into_owned())
+            }
+        };
+
+        let magic = next_token(&mut pos);
+        let mut header = [0u32; 3];
+        for value in &mut header {
+            *value = match next_token(&mut pos).and_then(|t| t.parse().ok()) {
+                Some(v) => v,
+                None => return Err(ImageError::new("invalid PPM header")),
+            };
+        }
+        let (width, height, maxval) = (header[0], header[1], header[2]);
+        if maxval == 0 || maxval > 65535 {
+            return Err(ImageError::new("invalid maximum value in PPM header"));
+        }
+        // Every value takes at least one byte, so the size in the header
+        // can't be bigger than the rest of the data. This is checked before
+        // allocating memory for the pixels.
+        let num_values = (width as usize).checked_mul(height as usize)
+            .and_then(|n| n.checked_mul(3))
+            .filter(|&n| n <= data.len() - cmp::min(pos, data.len()));
+        let num_values = match num_values {
+            Some(n) => n,
+            None => return Err(ImageError::new("PPM image data is too short")),
+        };
+        let scale = |v: u32| {
+            ((cmp::min(v, maxval) * 255 + maxval / 2) / maxval) as u8
+        };
+
+        let mut values = Vec::with_capacity(num_values);
+        match magic.as_deref() {
+            Some("P6") => {
+                // exactly one whitespace character after the header
+                let start = pos + 1;
+                let bytes = if maxval < 256 { 1 } else { 2 };
+                let raw = &data[cmp::min(start, data.len())..];
+                if raw.len() < num_values * bytes {
+                    return Err(ImageError::new("PPM image data is too short"));
+                }
+                for i in 0..num_values {
+                    let v = if bytes == 1 {
+                        raw[i] as u32
+                    } else {
+                        (raw[2 * i] as u32) << 8 | raw[2 * i + 1] as u32
+                    };
+                    values.push(scale(v));
+                }
+            }
+            Some("P3") => {
+                for _ in 0..num_values {
+                    match next_token(&mut pos).and_then(|t| t.parse().ok()) {
+                        Some(v) => values.push(scale(v)),
+                        None => {
+                            return Err(
+                                ImageError::new("invalid PPM image data")
+                            );
+                        }
+                    }
+                }
+            }
+            _ => return Err(ImageError::new("not a PPM image")),
+        }
+
+        let pixels = values.chunks(3).map(|p| [p[0], p[1], p[2]]).collect();
+        Ok(Self::new(width, height, pixels))
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Returns the color of the given pixel.
+    pub fn pixel(&self, x: u32, y: u32) -> Rgb {
+        self.pixels[y as usize * self.width as usize + x as usize]
+    }
+
+    /// Returns all pixels, row by row.
+    pub fn pixels(&self) -> &[Rgb] {
+        &self.pixels
+    }
+
+    /// Encodes the image as PNG.
+    pub fn write_png<W: Write>(&self, w: W) -> Result<(), ImageError> {
+        let mut encoder = png::Encoder::new(w, self.width, self.height);
+        encoder.set(png::ColorType::RGB).set(png::BitDepth::Eight);
+        let data: Vec<u8> = self.pixels.iter()
+            .flat_map(|p| p.iter().cloned())
+            .collect();
+
+        let res = encoder.write_header()
+            .and_then(|mut writer| writer.write_image_data(&data));
+        res.map_err(|e| ImageError::new(format!("couldn't write PNG: {}", e)))
+    }
+
+    /// Encodes the image as binary PPM (`P6`).
+    pub fn write_ppm<W: Write>(&self, mut w: W) -> Result<(), ImageError> {
+        write!(w, "P6\n{} {}\n255\n", self.width, self.height)?;
+        for p in &self.pixels {
+            w.write_all(p)?;
+        }
+        Ok(())
+    }
+
+    /// Writes the image to the given file. The format is determined by the
+    /// extension (`.png` or `.ppm`).
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), ImageError> {
+        let path = path.as_ref();
+        let ext = path.extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_lowercase());
+        match ext.as_deref() {
+            Some("png") => self.write_png(File::create(path)?),
+            Some("ppm") => self.write_ppm(File::create(path)?),
+            _ => Err(ImageError::new(format!(
+                "unknown image format of '{}' (use '.png' or '.ppm')",
+                path.display(),
+            ))),
+        }
+    }
+}
+
+/// Returns the color returned by `Color::as_rgb` with 8 bits per channel.
+pub fn to_rgb(c: Color) -> Rgb {
+    let c = c.as_rgb();
+    [
+        (c[0] * 255.0).round() as u8,
+        (c[1] * 255.0).round() as u8,
+        (c[2] * 255.0).round() as u8,
+    ]
+}
+
+/// Returns the default palette with the given number of colors, made of the
+/// colors returned by `Color::as_rgb`.
+pub fn default_palette(colors: u8) -> Vec<Rgb> {
+    (0..colors).map(|tag| to_rgb(Color::new(tag))).collect()
+}
+
+/// Parses a palette given as comma separated list of hex colors, like
+/// `e74c3c,27ae60,f1c40f`. A leading `#` is allowed for every color.
+pub fn parse_palette(s: &str) -> Result<Vec<Rgb>, ImageError> {
+    let palette = s.split(',').map(|c| {
+        let c = c.trim();
+        let hex = c.strip_prefix('#').unwrap_or(c);
+        match u32::from_str_radix(hex, 16) {
+            Ok(v) if hex.len() == 6 => {
+                Ok([(v >> 16) as u8, (v >> 8) as u8, v as u8])
+            }
+            _ => Err(ImageError::new(format!("invalid palette color '{}'", c))),
+        }
+    }).collect::<Result<Vec<_>, _>>()?;
+
+    if palette.len() > MAX_COLORS as usize {
+        return Err(ImageError::new(
+            format!("a palette can have at most {} colors", MAX_COLORS)
+        ));
+    }
+    Ok(palette)
+}
+
+/// Options for importing a board from an image.
+#[derive(Clone, Debug)]
+pub struct ImportOptions {
+    /// Number of columns of the board.
+    pub width: u16,
+    /// Number of rows of the board.
+    pub height: u16,
+    /// The colors of the board. The i-th entry is the color with tag i.
+    pub palette: Vec<Rgb>,
+    /// The part of the image containing the board as `(x, y, width,
+    /// height)` in pixels. `None` means the whole image.
+    pub area: Option<(u32, u32, u32, u32)>,
+    /// A cell is ambiguous if the distance to the nearest palette entry is
+    /// bigger than this factor times the distance to the second nearest
+    /// entry. Has to be between 0 and 1.
+    pub ambiguity_ratio: f32,
+}
+
+impl ImportOptions {
+    /// Returns options for a board of the given size with the default palette
+    /// and the whole image as board.
+    pub fn new(width: u16, height: u16, colors: u8) -> Self {
+        ImportOptions {
+            width: width,
+            height: height,
+            palette: default_palette(colors),
+            area: None,
+            ambiguity_ratio: 0.7,
+        }
+    }
+}
+
+/// Result of an import.
+#[derive(Clone)]
+pub struct Import {
+    pub board: Board,
+    /// All cells that couldn't be mapped to a palette color with certainty.
+    /// The cells are still part of the board (with the nearest color).
+    pub ambiguous: Vec<Pos>,
+}
+
+/// Imports a board from the given image.
+pub fn import_board(img: &Image, options: &ImportOptions)
+    -> Result<Import, ImageError>
+{
+    let palette = &options.palette;
+    if palette.is_empty() || palette.len() > MAX_COLORS as usize {
+        return Err(ImageError::new(
+            format!("a palette needs 1 to {} colors", MAX_COLORS)
+        ));
+    }
+    if options.width == 0 || options.height == 0 {
+        return Err(ImageError::new("the board has to have at least one cell"));
+    }
+
+    let (ax, ay, aw, ah) = options.area
+        .unwrap_or((0, 0, img.width, img.height));
+    let (right, bottom) = match (ax.checked_add(aw), ay.checked_add(ah)) {
+        (Some(r), Some(b)) if r <= img.width && b <= img.height => (r, b),
+        _ => {
+            return Err(
+                ImageError::new("the board area is outside of the image")
+            );
+        }
+    };
+    if aw < options.width as u32 || ah < options.height as u32 {
+        return Err(ImageError::new("the image is too small for the board"));
+    }
+
+    let cell_width = aw as f32 / options.width as f32;
+    let cell_height = ah as f32 / options.height as f32;
+
+    let mut cells = Vec::with_capacity(
+        options.width as usize * options.height as usize
+    );
+    let mut ambiguous = Vec::new();
+    for y in 0..options.height {
+        for x in 0..options.width {
+            // We average the pixels in the middle half of the cell to ignore
+            // borders and anti-aliasing between cells.
+            let x0 = ax as f32 + (x as f32 + 0.25) * cell_width;
+            let y0 = ay as f32 + (y as f32 + 0.25) * cell_height;
+            let x1 = (x0 + cell_width / 2.0).max(x0 + 1.0);
+            let y1 = (y0 + cell_height / 2.0).max(y0 + 1.0);
+
+            let mut sum = [0u64; 3];
+            let mut count = 0;
+            for py in (y0 as u32)..cmp::min(y1 as u32, bottom) {
+                for px in (x0 as u32)..cmp::min(x1 as u32, right) {
+                    let p = img.pixel(px, py);
+                    for i in 0..3 {
+                        sum[i] += p[i] as u64;
+                    }
+                    count += 1;
+                }
+            }
+            let count = cmp::max(count, 1);
+            let avg = [
+                (sum[0] / count) as u8,
+                (sum[1] / count) as u8,
+                (sum[2] / count) as u8,
+            ];
+
+            let (tag, is_ambiguous) =
+                nearest(palette, avg, options.ambiguity_ratio);
+            if is_ambiguous {
+                ambiguous.push((x, y));
+            }
+            cells.push(Color::new(tag));
+        }
+    }
+
+    Ok(Import {
+        board: Board::from_cells(
+            options.width,
+            options.height,
+            palette.len() as u8,
+            cells,
+        ),
+        ambiguous: ambiguous,
+    })
+}
+
+fn is_space(b: u8) -> bool {
+    (b as char).is_whitespace()
+}
+
+/// Returns the index of the palette entry nearest to `c` and whether or not
+/// the choice is ambiguous.
+fn nearest(palette: &[Rgb], c: Rgb, ratio: f32) -> (u8, bool) {
+    let dist = |p: &Rgb| -> f32 {
+        (0..3).map(|i| (p[i] as f32 - c[i] as f32).powi(2)).sum::<f32>().sqrt()
+    };
+
+    let mut best = (0, ::std::f32::INFINITY);
+    let mut second = ::std::f32::INFINITY;
+    for (i, p) in palette.iter().enumerate() {
+        let d = dist(p);
+        if d < best.1 {
+            second = best.1;
+            best = (i, d);
+        } else if d < second {
+            second = d;
+        }
+    }
+
+    (best.0 as u8, best.1 > ratio * second)
+}
@@ -0,0 +1,295 @@
+//! JSON representation of boards, solutions and game records.
+//!
+//! Every top level JSON object contains a `"version"` field. Documents with a
+//! newer version than `FORMAT_VERSION` are rejected. The formats are:
+//!
+//! - board: `{"version": 1, "width": 3, "height": 2, "colors": 6,
+//!   "rows": ["012", "345"]}` (each row is written as in the `text` format)
+//! - solution: `{"version": 1, "moves": [0, 3, 1]}`
+//! - game record: `{"version": 1, "board": <board>, "generator": "deter0",
+//!   "seed": 7, "player": "exact", "moves": [0, 3, 1], "solved": true}`
+//!   (`seed` is `null` for non deterministic generators)
+//! - list of game records: `{"version": 1, "games": [<game record>, ...]}`
+
+use board::Board;
+use color::{Color, MAX_COLORS};
+use solver::Solution;
+use rustc_serialize::json::{self, Json, ToJson};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+
+
+/// The current version of the JSON formats.
+pub const FORMAT_VERSION: u64 = 1;
+
+/// Error returned when a JSON document can't be parsed or doesn't have the
+/// expected structure.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct JsonError {
+    pub msg: String,
+}
+
+impl JsonError {
+    fn new<S: Into<String>>(msg: S) -> Self {
+        JsonError {
+            msg: msg.into(),
+        }
+    }
+}
+
+impl fmt::Display for JsonError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.msg.fmt(f)
+    }
+}
+
+impl Error for JsonError {
+    fn description(&self) -> &str {
+        &self.msg
+    }
+}
+
+/// Types which can be created from their JSON representation.
+pub trait FromJson: Sized {
+    fn from_json(json: &Json) -> Result<Self, JsonError>;
+
+    /// Parses the given string as JSON document and converts it.
+    fn from_json_str(s: &str) -> Result<Self, JsonError> {
+        Self::from_json(&parse(s)?)
+    }
+}
+
+/// One played game: the initial board, how it was created, who played and
+/// the moves that were made.
+#[derive(Clone)]
+pub struct GameRecord {
+    pub board: Board,
+    pub generator: String,
+    pub seed: Option<u64>,
+    pub player: String,
+    pub moves: Solution,
+    pub solved: bool,
+}
+
+impl ToJson for Color {
+    fn to_json(&self) -> Json {
+        Json::U64(self.tag as u64)
+    }
+}
+
+impl ToJson for Board {
+    fn to_json(&self) -> Json {
+        let rows = (0..self.height()).map(|y| {
+            let row: String = (0..self.width())
+                .map(|x| format!("{:x}", self[(x, y)].tag))
+                .collect();
+            Json::String(row)
+        }).collect();
+
+        let mut obj = versioned_object();
+        obj.insert("width".into(), self.width().to_json());
+        obj.insert("height".into(), self.height().to_json());
+        obj.insert("colors".into(), self.colors().to_json());
+        obj.insert("rows".into(), Json::Array(rows));
+        Json::Object(obj)
+    }
+}
+
+impl FromJson for Board {
+    fn from_json(json: &Json) -> Result<Self, JsonError> {
+        check_version(json)?;
+        let width = get_u64(json, "width")?;
+        let height = get_u64(json, "height")?;
+        let colors = get_u64(json, "colors")?;
+        let rows = get_array(json, "rows")?;
+
+        let max = ::std::u16::MAX as u64;
+        if width == 0 || height == 0 || width > max || height > max {
+            return Err(JsonError::new(
+                format!("invalid board dimensions {}x{}", width, height)
+            ));
+        }
+        if colors == 0 || colors > MAX_COLORS as u64 {
+            return Err(JsonError::new(
+                format!("invalid number of colors {}", colors)
+            ));
+        }
+        if rows.len() as u64 != height {
+            return Err(JsonError::new(format!(
+                "board has {} rows, but a height of {}",
+                rows.len(),
+                height,
+            )));
+        }
+
+        let mut cells = Vec::with_capacity((width * height) as usize);
+        for (y, row) in rows.iter().enumerate() {
+            let row = row.as_string().ok_or_else(|| {
+                JsonError::new(format!("row {} is not a string", y))
+            })?;
+            if row.chars().count() as u64 != width {
+                return Err(JsonError::new(format!(
+                    "row {} has {} cells, but the board has a width of {}",
+                    y,
+                    row.chars().count(),
+                    width,
+                )));
+            }
+
+            for c in row.chars() {
+                match c.to_digit(16) {
+                    Some(tag) if (tag as u64) < colors => {
+                        cells.push(Color::new(tag as u8));
+                    }
+                    _ => return Err(JsonError::new(
+                        format!("invalid cell '{}' in row {}", c, y)
+                    )),
+                }
+            }
+        }
+
+        Ok(Board::from_cells(width as u16, height as u16, colors as u8, cells))
+    }
+}
+
+impl ToJson for GameRecord {
+    fn to_json(&self) -> Json {
+        let mut obj = versioned_object();
+        obj.insert("board".into(), self.board.to_json());
+        obj.insert("generator".into(), self.generator.to_json());
+        obj.insert("seed".into(), self.seed.to_json());
+        obj.insert("player".into(), self.player.to_json());
+        obj.insert("moves".into(), self.moves.to_json());
+        obj.insert("solved".into(), self.solved.to_json());
+        Json::Object(obj)
+    }
+}
+
+impl FromJson for GameRecord {
+    fn from_json(json: &Json) -> Result<Self, JsonError> {
+        check_version(json)?;
+        let board = Board::from_json(get(json, "board")?)?;
+        let moves = moves_from_json(json, board.colors())?;
+
+        let seed = match get(json, "seed")? {
+            &Json::Null => None,
+            other => Some(other.as_u64().ok_or_else(|| {
+                JsonError::new("field 'seed' is not a number")
+            })?),
+        };
+
+        Ok(GameRecord {
+            board: board,
+            generator: get_string(json, "generator")?,
+            seed: seed,
+            player: get_string(json, "player")?,
+            moves: moves,
+            solved: get_bool(json, "solved")?,
+        })
+    }
+}
+
+/// Returns the JSON representation of a solution.
+pub fn solution_to_json(moves: &[Color]) -> Json {
+    let mut obj = versioned_object();
+    obj.insert("moves".into(), moves.to_json());
+    Json::Object(obj)
+}
+
+/// Reads a solution from its JSON representation. All moves have to be
+/// colors of a board with `colors` colors.
+pub fn solution_from_json(json: &Json, colors: u8)
+    -> Result<Solution, JsonError>
+{
+    check_version(json)?;
+    moves_from_json(json, colors)
+}
+
+/// Returns the JSON representation of a list of game records.
+pub fn games_to_json(games: &[GameRecord]) -> Json {
+    let mut obj = versioned_object();
+    obj.insert("games".into(), games.to_json());
+    Json::Object(obj)
+}
+
+/// Reads a list of game records from its JSON representation.
+pub fn games_from_json(json: &Json) -> Result<Vec<GameRecord>, JsonError> {
+    check_version(json)?;
+    get_array(json, "games")?.iter()
+        .map(GameRecord::from_json)
+        .collect()
+}
+
+/// Encodes the given JSON value as pretty printed string.
+pub fn to_pretty_string(json: &Json) -> String {
+    json.pretty().to_string()
+}
+
+/// Parses the given string as JSON document.
+pub fn parse(s: &str) -> Result<Json, JsonError> {
+    Json::from_str(s).map_err(|e| {
+        JsonError::new(format!("invalid JSON: {}", e))
+    })
+}
+
+fn versioned_object() -> json::Object {
+    let mut obj = BTreeMap::new();
+    obj.insert("version".into(), FORMAT_VERSION.to_json());
+    obj
+}
+
+fn check_version(json: &Json) -> Result<(), JsonError> {
+    let version = get_u64(json, "version")?;
+    if version > FORMAT_VERSION {
+        Err(JsonError::new(format!(
+            "unsupported format version {} (only versions up to {} are \
+             supported)",
+            version,
+            FORMAT_VERSION,
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+fn moves_from_json(json: &Json, colors: u8) -> Result<Solution, JsonError> {
+    get_array(json, "moves")?.iter().map(|m| {
+        match m.as_u64() {
+            Some(tag) if tag < colors as u64 => Ok(Color::new(tag as u8)),
+            _ => Err(JsonError::new(format!("invalid move {}", m))),
+        }
+    }).collect()
+}
+
+fn get<'a>(json: &'a Json, key: &str) -> Result<&'a Json, JsonError> {
+    json.find(key).ok_or_else(|| {
+        JsonError::new(format!("missing field '{}'", key))
+    })
+}
+
+fn get_u64(json: &Json, key: &str) -> Result<u64, JsonError> {
+    get(json, key)?.as_u64().ok_or_else(|| {
+        JsonError::new(format!("field '{}' is not a number", key))
+    })
+}
+
+fn get_bool(json: &Json, key: &str) -> Result<bool, JsonError> {
+    get(json, key)?.as_boolean().ok_or_else(|| {
+        JsonError::new(format!("field '{}' is not a boolean", key))
+    })
+}
+
+fn get_string(json: &Json, key: &str) -> Result<String, JsonError> {
+    get(json, key)?.as_string().map(|s| s.to_string()).ok_or_else(|| {
+        JsonError::new(format!("field '{}' is not a string", key))
+    })
+}
+
+fn get_array<'a>(json: &'a Json, key: &str)
+    -> Result<&'a json::Array, JsonError>
+{
+    get(json, key)?.as_array().ok_or_else(|| {
+        JsonError::new(format!("field '{}' is not an array", key))
+    })
+}
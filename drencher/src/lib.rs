@@ -0,0 +1,204 @@
+//! Implementation of the game drench.
+//!
+//! This crate contains the game itself (the `Board` with its `Color`s), a
+//! few solvers/players (see the `solver` module) and a benchmarking mode to
+//! compare those solvers. The `drencher` binary is just a thin CLI on top of
+//! this library.
+
+extern crate rand;
+extern crate term_painter;
+extern crate time;
+extern crate rayon;
+extern crate pbr;
+#[macro_use] extern crate log;
+#[cfg(feature = "gui")]
+#[macro_use] extern crate glium;
+extern crate smallvec;
+extern crate bit_set;
+extern crate rustc_serialize;
+extern crate png;
+extern crate gif;
+extern crate deflate;
+
+#[macro_use]
+pub mod util;
+pub mod error;
+pub mod color;
+pub mod board;
+pub mod graph;
+pub mod text;
+pub mod json;
+pub mod id;
+pub mod image;
+pub mod render;
+pub mod animation;
+pub mod verify;
+pub mod notation;
+pub mod solver;
+pub mod bench;
+
+pub use error::Error;
+pub use color::Color;
+pub use board::{Board, Pos};
+pub use graph::RegionGraph;
+pub use text::ParseBoardError;
+pub use id::ParseIdError;
+pub use notation::ParseMovesError;
+pub use solver::{Solver, Solution, SolveOutcome};
+pub use bench::run_benchmark;
+
+use std::fs::File;
+use std::io::Read;
+
+
+/// Generates a board with the given dimensions and number of colors by using
+/// the initial board algorithm with the name `init_algo`. The `id` is used by
+/// deterministic algorithms to generate different boards.
+///
+/// If `init_algo` is `file:<path>`, the board is loaded from the given file
+/// (see the `text` module for the format) instead. With `json:<path>` the
+/// board is loaded from a JSON file containing either a board or a game
+/// record (see the `json` module). In both cases, the dimensions and number
+/// of colors are taken from the file. `id:<board-id>` creates the board with
+/// the given ID (see the `id` module). Finally, `image:<path>` imports the
+/// board from a PNG or PPM image showing a board with the given dimensions
+/// and number of colors (see the `image` module).
+pub fn gen_board(
+    init_algo: &str,
+    width: u16,
+    height: u16,
+    colors: u8,
+    id: u64,
+) -> Result<Board, Error> {
+    if width == 0 || height == 0 {
+        return Err(Error::InvalidSize(format!(
+            "a board has to have at least one row and column, but {}x{} \
+             was requested",
+            width,
+            height,
+        )));
+    }
+    if colors == 0 || colors > color::MAX_COLORS {
+        return Err(Error::InvalidSize(format!(
+            "the number of colors has to be between 1 and {}, but {} was \
+             requested",
+            color::MAX_COLORS,
+            colors,
+        )));
+    }
+
+    match init_algo {
+        "random" => Ok(Board::random(width, height, colors)),
+        "deter0" => {
+            Ok(Board::deterministic_random(width, height, colors, id))
+        }
+        "uniform" => Ok(Board::uniform(width, height, colors)),
+        "permutations" => {
+            Ok(Board::permutation(width, height, colors, id))
+        }
+        other if other.starts_with("file:") => {
+            load_board(&other["file:".len()..])
+        }
+        other if other.starts_with("json:") => {
+            load_json_board(&other["json:".len()..])
+        }
+        other if other.starts_with("image:") => {
+            load_image_board(&other["image:".len()..], width, height, colors)
+        }
+        other if other.starts_with("id:") => {
+            Board::from_id(&other["id:".len()..]).map_err(|e| {
+                Error::parse("board ID", e)
+            })
+        }
+        other => Err(Error::UnknownGenerator(other.to_string())),
+    }
+}
+
+/// Returns the seed of the given initial board algorithm for the board with
+/// the given id, or `None` if the algorithm doesn't use the id.
+pub fn generator_seed(init_algo: &str, id: u64) -> Option<u64> {
+    match init_algo {
+        "deter0" | "permutations" => Some(id),
+        _ => None,
+    }
+}
+
+fn read_file(path: &str) -> Result<String, Error> {
+    let mut content = String::new();
+    let res = File::open(path).and_then(|mut f| f.read_to_string(&mut content));
+    match res {
+        Ok(_) => Ok(content),
+        Err(e) => Err(Error::io(path, e)),
+    }
+}
+
+/// Loads a board in the text format from the given file.
+fn load_board(path: &str) -> Result<Board, Error> {
+    let content = read_file(path)?;
+    content.parse().map_err(|e: ParseBoardError| {
+        Error::parse(format!("board file '{}'", path), e)
+    })
+}
+
+/// Loads a board from a JSON file containing a board or a game record.
+fn load_json_board(path: &str) -> Result<Board, Error> {
+    use json::FromJson;
+
+    let content = read_file(path)?;
+    let res = json::parse(&content).and_then(|json| {
+        match json.find("board") {
+            Some(board) => Board::from_json(board),
+            None => Board::from_json(&json),
+        }
+    });
+
+    res.map_err(|e| Error::parse(format!("board file '{}'", path), e))
+}
+
+/// Imports a board from the given image with the default palette. Cells with
+/// an ambiguous color are reported.
+fn load_image_board(path: &str, width: u16, height: u16, colors: u8)
+    -> Result<Board, Error>
+{
+    let options = image::ImportOptions::new(width, height, colors);
+    let import = image::Image::open(path)
+        .and_then(|img| image::import_board(&img, &options))
+        .map_err(|e| Error::parse(format!("image '{}'", path), e))?;
+
+    if !import.ambiguous.is_empty() {
+        warn!(
+            "the color of {} cell(s) in '{}' is ambiguous:",
+            import.ambiguous.len(),
+            path,
+        );
+        for &(x, y) in &import.ambiguous {
+            warn!(
+                "  ({}, {}) was read as color {}",
+                x,
+                y,
+                import.board[(x, y)].tag,
+            );
+        }
+    }
+    Ok(import.board)
+}
+
+/// Returns the player/solver with the given name. Without the `gui` feature,
+/// the player `human` doesn't exist and `PlayerUnavailable` is returned.
+pub fn get_player(name: &str) -> Result<Box<dyn Solver>, Error> {
+    match name {
+        #[cfg(feature = "gui")]
+        "human" => Ok(Box::new(solver::Human)),
+        #[cfg(not(feature = "gui"))]
+        "human" => Err(Error::PlayerUnavailable(name.to_string())),
+        "terminal" => Ok(Box::new(solver::Terminal)),
+        "exact" => Ok(Box::new(solver::Exact)),
+        "astar" => Ok(Box::new(solver::AStar)),
+        "iddfs" => Ok(Box::new(solver::IterativeDeepening::new())),
+        "bnb" => Ok(Box::new(solver::BranchAndBound)),
+        "random" => Ok(Box::new(solver::Random)),
+        "heuristic" => Ok(Box::new(solver::Heuristic)),
+        "modcount" => Ok(Box::new(solver::ModCount)),
+        other => Err(Error::UnknownPlayer(other.to_string())),
+    }
+}
@@ -1,66 +1,312 @@
 extern crate docopt;
-extern crate rand;
+extern crate serde;
+#[macro_use] extern crate serde_derive;
 extern crate rustc_serialize;
 extern crate term_painter;
-extern crate time;
-extern crate rayon;
-extern crate pbr;
-#[macro_use] extern crate log;
 extern crate env_logger;
-#[macro_use] extern crate glium;
-extern crate smallvec;
-extern crate bit_set;
-
-#[macro_use]
-mod util;
-mod color;
-mod board;
-mod solver;
-mod bench;
-
-use solver::Solver;
-use board::Board;
+extern crate drencher;
+extern crate time;
+extern crate atty;
+
 use docopt::Docopt;
 use term_painter::{ToStyle, Color};
-use bench::run_benchmark;
+use drencher::{gen_board, generator_seed, get_player, run_benchmark};
+use drencher::{Board, Error, RegionGraph, Solver};
+use drencher::color::MAX_COLORS;
+use drencher::graph::ROOT;
+use drencher::json::{self, FromJson, GameRecord};
+use drencher::render::{self, RenderOptions};
+use drencher::animation::{self, AnimationOptions};
+use drencher::solver::{CancelToken, Heuristic, Progress, SolveOutcome};
+use drencher::verify;
+use drencher::notation;
+use rustc_serialize::json::ToJson;
+use serde::de::DeserializeOwned;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::path::Path;
+use time::Duration;
 
 
 // USAGE-string used by docopt
-const USAGE: &'static str = "
+const USAGE: &str = "
 Drencher: implementation of the 'drench' game with AI- and human-players.
 
 Usage:
-  drencher [options] [<player>]
+  drencher [<command>] [<args>...]
   drencher (-h | --help)
   drencher --version
 
-Arguments:
-  player                The player/solver for the game.
+Commands:
+  play                  Play a game, either yourself or by watching a player.
+                        This is the default if no command is given.
+  solve                 Let a player solve a board and print the solution.
+  bench                 Benchmark a player on many boards.
+  generate              Generate boards and write them to files.
+  verify                Check whether a solution drenches a board.
+  analyze               Print statistics about a board.
+  help                  Show the help of a command.
+
+Run 'drencher help <command>' for more information on a command.
 
 Options:
   -h --help             Show this screen.
   --version             Show version.
-  --size=<size>         Side length of the board [default: 14].
-  --board=<initial>     Initial configuration of the board [default: random].
-  --bench=<count>       In the benchmarking mode the specified player <count>
-                        games are played and timing is measured. It's advised
-                        to use a deterministic initial board algorithm, like
-                        'deter0', or to use a fairly high repetition count.
-                        There is also no output of the board or the solution
-                        in this mode.
+
+Exit codes:
+  0                     Success.
+  1                     Invalid command line arguments.
+  2                     The board wasn't solved or the solution is invalid.
+  3                     Unknown or unavailable player.
+  4                     Unknown initial board algorithm.
+  5                     Invalid board size or number of colors.
+  6                     Invalid input (board file, ID, image, solution or
+                        timeout).
+  7                     A file couldn't be read or written.
+  8                     The solver failed.
+";
+
+// Options shared by all commands which create boards. They are appended to
+// the USAGE-strings of those commands.
+const BOARD_OPTIONS: &str = "
+Board options:
+  --size=<size>         Side length of the board (1 to 65535) [default: 14].
+  --width=<width>       Number of columns of the board (overrides --size).
+  --height=<height>     Number of rows of the board (overrides --size).
+  --colors=<colors>     Number of colors of the board (1 to 16) [default: 6].
+
+Boards are given by the name of an initial board algorithm ('random',
+'deter0', 'uniform' or 'permutations') or by their source: 'file:<path>' to
+load a board file, 'json:<path>' to load a JSON board or game record,
+'id:<board-id>' to use the board with the given ID or 'image:<path>' to import
+a board with the given size and colors from a PNG or PPM screenshot.
+";
+
+const PLAYERS: &str = "
+Players: 'human' (window), 'terminal', 'exact', 'astar', 'iddfs', 'bnb',
+'heuristic', 'random' and 'modcount'. 'exact', 'astar', 'iddfs' and 'bnb'
+always find optimal solutions; 'astar' is usually a lot faster on bigger
+boards, 'iddfs' needs only little memory and 'bnb' improves the solution of
+'heuristic'.
+";
+
+const USAGE_PLAY: &str = "
+Play a game: the player tries to solve the board, which is printed after
+every move.
+
+Usage:
+  drencher play [options] [<player>]
+  drencher play (-h | --help)
+
+Options:
+  -h --help             Show this screen.
+  --board=<initial>     Initial board [default: random].
+  --save=<path>         Save the initial board to the given file (in the
+                        same format as used by '--board=file:<path>').
+  --record=<path>       Save the played game as JSON game record to the
+                        given file.
+  --render=<path>       Render the initial board and the board after every
+                        move to the given image file ('.png', '.svg' or
+                        '.ppm').
+  --replay=<path>       Save an animation of the game with one frame per
+                        move to the given file ('.gif', '.png' or '.apng').
+  --delay=<ms>          Time every frame of the animation is shown, in
+                        milliseconds [default: 500].
+
+The default player is 'human'.
+
+Exit codes: 0 if the game was played, 3 or higher on errors (see
+'drencher --help').
+";
+
+const USAGE_SOLVE: &str = "
+Let a player solve a board and print the solution in the move notation (one
+hex digit per move, like '2031').
+
+Usage:
+  drencher solve [options] <board> [<player>]
+  drencher solve (-h | --help)
+
+Options:
+  -h --help             Show this screen.
+  --record=<path>       Save the game as JSON game record to the given file.
+  --timeout=<secs>      Stop the player after the given number of seconds
+                        and print the best partial solution found so far.
+
+The default player is 'exact'. Searching players (like 'exact') show a status
+line with their progress on stderr while solving, if stderr is a terminal.
+
+Exit codes: 0 if the board was solved, 2 if it wasn't, 3 or higher on errors
+(see 'drencher --help').
+";
+
+const USAGE_BENCH: &str = "
+Benchmark a player: <count> games are played and timing is measured. It's
+advised to use a deterministic initial board algorithm, like 'deter0', or to
+use a fairly high count. The boards and solutions are not printed.
+
+Usage:
+  drencher bench [options] <player> <count>
+  drencher bench (-h | --help)
+
+Options:
+  -h --help             Show this screen.
+  --board=<initial>     Initial board algorithm [default: random].
+  --record=<path>       Save the played games as JSON game records to the
+                        given file.
   --no-progress         Hide progress bar.
-  --no-threads          Disable threading
+  --no-threads          Disable threading.
+  --timeout=<secs>      Stop the player after the given number of seconds
+                        per board. Those runs count as not solved.
+
+Exit codes: 0 if the benchmark was run, 3 or higher on errors (see
+'drencher --help').
 ";
 
-#[derive(Debug, RustcDecodable)]
+const USAGE_GENERATE: &str = "
+Generate one board for every given path and write it to that file. The format
+is chosen by the extension: '.json' for a JSON board, '.png', '.svg' or
+'.ppm' for an image and the text format (as used by '--board=file:<path>')
+for everything else.
+
+Usage:
+  drencher generate [options] <path>...
+  drencher generate (-h | --help)
+
+Options:
+  -h --help             Show this screen.
+  --board=<initial>     Initial board algorithm [default: random].
+  --first-id=<id>       Deterministic algorithms get the ID <id> for the
+                        first board, <id> + 1 for the second board and so on
+                        [default: 0].
+
+Exit codes: 0 if all boards were written, 3 or higher on errors (see
+'drencher --help').
+";
+
+const USAGE_VERIFY: &str = "
+Replay a solution on a board and check whether it drenches the board. For
+every move it's shown whether it was legal, whether it was redundant (the
+field already had that color or nothing was captured) and how many cells it
+captured. The solution is either given in the move notation (one hex digit
+per move, like '2031') or as 'json:<path>' to load a JSON solution or game
+record.
+
+Usage:
+  drencher verify [options] <board> <solution>
+  drencher verify (-h | --help)
+
+Options:
+  -h --help             Show this screen.
+  --strict              Redundant moves make the solution invalid, too.
+  --quiet               Only print the summary, not every move.
+
+Exit codes: 0 if the solution is valid, 2 if it isn't (the board is not
+drenched or a move is illegal), 3 or higher on errors (see 'drencher --help').
+";
+
+const USAGE_ANALYZE: &str = "
+Print statistics about a board, like the number of regions and bounds for
+the number of moves needed to solve it.
+
+Usage:
+  drencher analyze [options] <board>
+  drencher analyze (-h | --help)
+
+Options:
+  -h --help             Show this screen.
+
+Exit codes: 0 if the board was analyzed, 3 or higher on errors (see
+'drencher --help').
+";
+
+/// Exit code for successful runs.
+const EXIT_SUCCESS: i32 = 0;
+/// Exit code for invalid command line arguments (like docopt uses).
+const EXIT_USAGE: i32 = 1;
+/// Exit code if a board wasn't solved or a solution isn't valid.
+const EXIT_UNSOLVED: i32 = 2;
+
+#[derive(Debug, Deserialize)]
 struct Args {
-    arg_player: Option<String>,
+    arg_command: Option<String>,
+    arg_args: Vec<String>,
     flag_version: bool,
-    flag_size: u8, // TODO: nice error message when input is too big
+}
+
+#[derive(Debug, Deserialize)]
+struct PlayArgs {
+    arg_player: Option<String>,
+    flag_size: usize,
+    flag_width: Option<usize>,
+    flag_height: Option<usize>,
+    flag_colors: u8,
+    flag_board: String,
+    flag_save: Option<String>,
+    flag_record: Option<String>,
+    flag_render: Option<String>,
+    flag_replay: Option<String>,
+    flag_delay: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct SolveArgs {
+    arg_board: String,
+    arg_player: Option<String>,
+    flag_size: usize,
+    flag_width: Option<usize>,
+    flag_height: Option<usize>,
+    flag_colors: u8,
+    flag_record: Option<String>,
+    flag_timeout: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BenchArgs {
+    arg_player: String,
+    arg_count: usize,
+    flag_size: usize,
+    flag_width: Option<usize>,
+    flag_height: Option<usize>,
+    flag_colors: u8,
     flag_board: String,
-    flag_bench: Option<usize>,
+    flag_record: Option<String>,
     flag_no_progress: bool,
     flag_no_threads: bool,
+    flag_timeout: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GenerateArgs {
+    arg_path: Vec<String>,
+    flag_size: usize,
+    flag_width: Option<usize>,
+    flag_height: Option<usize>,
+    flag_colors: u8,
+    flag_board: String,
+    flag_first_id: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct VerifyArgs {
+    arg_board: String,
+    arg_solution: String,
+    flag_strict: bool,
+    flag_quiet: bool,
+    flag_size: usize,
+    flag_width: Option<usize>,
+    flag_height: Option<usize>,
+    flag_colors: u8,
+}
+
+#[derive(Debug, Deserialize)]
+struct AnalyzeArgs {
+    arg_board: String,
+    flag_size: usize,
+    flag_width: Option<usize>,
+    flag_height: Option<usize>,
+    flag_colors: u8,
 }
 
 
@@ -70,7 +316,7 @@ fn main() {
 
     // read and parse CLI-args, exit if any error occured
     let args: Args = Docopt::new(USAGE)
-                            .and_then(|d| d.decode())
+                            .and_then(|d| d.options_first(true).deserialize())
                             .unwrap_or_else(|e| e.exit());
 
     // if the version flag was set, we just print the version and exit
@@ -79,93 +325,607 @@ fn main() {
         return;
     }
 
+    // Without a command, a game is played like before the commands existed
+    let command = args.arg_command.unwrap_or_else(|| "play".to_string());
+    let mut argv = vec!["drencher".to_string(), command.clone()];
+    argv.extend(args.arg_args);
+
+    let res = match command.as_str() {
+        "play" => cmd_play(parse_args(&argv)),
+        "solve" => cmd_solve(parse_args(&argv)),
+        "bench" => cmd_bench(parse_args(&argv)),
+        "generate" => cmd_generate(parse_args(&argv)),
+        "verify" => cmd_verify(parse_args(&argv)),
+        "analyze" => cmd_analyze(parse_args(&argv)),
+        "help" => {
+            match argv.get(2).and_then(|c| usage_of(c)) {
+                Some(usage) => println!("{}", usage.trim()),
+                None => println!("{}", USAGE.trim()),
+            }
+            Ok(EXIT_SUCCESS)
+        }
+        other => {
+            print_error(format!(
+                "unknown command '{}'. Run 'drencher --help' for a list of \
+                 all commands.",
+                other,
+            ));
+            std::process::exit(EXIT_USAGE);
+        }
+    };
+
+    match res {
+        Ok(code) => std::process::exit(code),
+        Err(e) => {
+            print_error(&e);
+            std::process::exit(exit_code(&e));
+        }
+    }
+}
+
+/// Prints the error message to stderr. `term_painter` always writes its
+/// colors to stdout, so the red label is written by hand.
+fn print_error<T: fmt::Display>(msg: T) {
+    let label = if atty::is(atty::Stream::Stderr) {
+        "\x1b[31mError\x1b[0m"
+    } else {
+        "Error"
+    };
+    let _ = writeln!(io::stderr(), "{}: {}", label, msg);
+}
+
+/// Returns the exit code for the given error (see USAGE).
+fn exit_code(e: &Error) -> i32 {
+    match *e {
+        Error::UnknownPlayer(_) | Error::PlayerUnavailable(_) => 3,
+        Error::UnknownGenerator(_) => 4,
+        Error::InvalidSize(_) => 5,
+        Error::Parse { .. } => 6,
+        Error::Io { .. } => 7,
+        Error::Solver(_) => 8,
+    }
+}
+
+/// Returns the full USAGE-string of the given command.
+fn usage_of(command: &str) -> Option<String> {
+    let (usage, players, board_options) = match command {
+        "play" => (USAGE_PLAY, true, true),
+        "solve" => (USAGE_SOLVE, true, true),
+        "bench" => (USAGE_BENCH, true, true),
+        "generate" => (USAGE_GENERATE, false, true),
+        "verify" => (USAGE_VERIFY, false, true),
+        "analyze" => (USAGE_ANALYZE, false, true),
+        _ => return None,
+    };
+
+    let mut out = usage.to_string();
+    if players {
+        out.push_str(PLAYERS);
+    }
+    if board_options {
+        out.push_str(BOARD_OPTIONS);
+    }
+    Some(out)
+}
+
+/// Parses the arguments of a command (`argv[1]`). On errors (or if the help
+/// was requested) the process is terminated.
+fn parse_args<T: DeserializeOwned>(argv: &[String]) -> T {
+    let usage = usage_of(&argv[1]).expect("unknown command");
+    Docopt::new(usage)
+        .and_then(|d| d.argv(argv.iter()).deserialize())
+        .unwrap_or_else(|e| e.exit())
+}
+
+/// Checks the board options and returns the width, height and number of
+/// colors.
+fn board_params(
+    size: usize,
+    width: Option<usize>,
+    height: Option<usize>,
+    colors: u8,
+) -> Result<(u16, u16, u8), Error> {
+    if colors == 0 || colors > MAX_COLORS {
+        return Err(Error::InvalidSize(format!(
+            "the number of colors has to be between 1 and {}",
+            MAX_COLORS,
+        )));
+    }
+
+    let width = check_dimension("width", width.unwrap_or(size))?;
+    let height = check_dimension("height", height.unwrap_or(size))?;
+    Ok((width, height, colors))
+}
+
+/// Checks that the given board dimension is in the supported range and
+/// returns it.
+fn check_dimension(name: &str, value: usize) -> Result<u16, Error> {
+    let max = std::u16::MAX as usize;
+    if value == 0 || value > max {
+        return Err(Error::InvalidSize(format!(
+            "the {} of the board has to be between 1 and {}, but {} was given",
+            name,
+            max,
+            value,
+        )));
+    }
+
+    Ok(value as u16)
+}
+
+
+/// Writes the game record as JSON to the given file.
+fn save_record(path: &str, record: &GameRecord) -> Result<(), Error> {
+    let res = File::create(path).and_then(|mut f| {
+        writeln!(f, "{}", json::to_pretty_string(&record.to_json()))
+    });
+    res.map_err(|e| Error::io(path, e))
+}
+
+/// Boards with more cells don't get their ID printed, as it would be
+/// thousands of characters long.
+const MAX_PRINTED_ID_CELLS: usize = 100 * 100;
+
+/// Returns the ID of the board for printing or a note if it's too long.
+fn printable_id(board: &Board) -> String {
+    let cells = board.width() as usize * board.height() as usize;
+    if cells <= MAX_PRINTED_ID_CELLS {
+        board.to_id()
+    } else {
+        format!(
+            "(not shown for boards with more than {} cells)",
+            MAX_PRINTED_ID_CELLS,
+        )
+    }
+}
+
+
+// ===========================================================================
+// Commands
+// ===========================================================================
+
+fn cmd_play(args: PlayArgs) -> Result<i32, Error> {
+    let (width, height, colors) = board_params(
+        args.flag_size,
+        args.flag_width,
+        args.flag_height,
+        args.flag_colors,
+    )?;
     let player = args.arg_player.unwrap_or("human".into());
 
-    let res = if let Some(count) = args.flag_bench {
-        if player == "human" {
+    play_standard_mode(
+        &args.flag_board,
+        width,
+        height,
+        colors,
+        &player,
+        args.flag_save.as_deref(),
+        args.flag_record.as_deref(),
+        args.flag_render.as_deref(),
+        args.flag_replay.as_deref(),
+        args.flag_delay,
+    )?;
+    Ok(EXIT_SUCCESS)
+}
+
+fn cmd_solve(args: SolveArgs) -> Result<i32, Error> {
+    let (width, height, colors) = board_params(
+        args.flag_size,
+        args.flag_width,
+        args.flag_height,
+        args.flag_colors,
+    )?;
+    let player_name = args.arg_player.unwrap_or("exact".into());
+    let player = get_player(&player_name)?;
+    let board = gen_board(&args.arg_board, width, height, colors, 0)?;
+
+    let cancel = timeout(args.flag_timeout)?
+        .map(CancelToken::with_timeout)
+        .unwrap_or_default();
+
+    // show the progress of searching solvers in a status line which is
+    // overwritten by every report. It goes to stderr and only if that's a
+    // terminal, so that the output can be piped and logged.
+    let show_status = atty::is(atty::Stream::Stderr);
+    let mut status_shown = false;
+    let outcome = {
+        let mut report = |p: &Progress| {
+            if !show_status {
+                return;
+            }
+            let mut stderr = io::stderr();
+            let _ = write!(
+                stderr,
+                "\rDepth {}: {} states, {} pruned ({:.1}s)   ",
+                p.depth,
+                p.frontier,
+                p.pruned,
+                (p.elapsed.num_milliseconds() as f64) / 1000.0,
+            );
+            let _ = stderr.flush();
+            status_shown = true;
+        };
+        player.solve_with_progress(board.clone(), &cancel, &mut report)
+    };
+    if status_shown {
+        let _ = writeln!(io::stderr());
+    }
+
+    match outcome.failure {
+        None => println!(
+            "Solved in {} moves{}: {}",
+            outcome.moves.len(),
+            if outcome.optimal { " (optimal)" } else { "" },
+            notation::format_moves(&outcome.moves),
+        ),
+        Some(reason) => println!(
+            "NOT solved after {} moves ({}): {}",
+            outcome.moves.len(),
+            reason,
+            notation::format_moves(&outcome.moves),
+        ),
+    }
+    print_search_stats(&outcome);
+    let solved = outcome.is_solved();
+
+    if let Some(path) = args.flag_record {
+        save_record(&path, &GameRecord {
+            board: board,
+            generator: args.arg_board.clone(),
+            seed: generator_seed(&args.arg_board, 0),
+            player: player_name,
+            moves: outcome.moves,
+            solved: solved,
+        })?;
+    }
+
+    Ok(if solved { EXIT_SUCCESS } else { EXIT_UNSOLVED })
+}
+
+fn cmd_bench(args: BenchArgs) -> Result<i32, Error> {
+    let (width, height, colors) = board_params(
+        args.flag_size,
+        args.flag_width,
+        args.flag_height,
+        args.flag_colors,
+    )?;
+
+    let player = args.arg_player;
+    if player == "human" || player == "terminal" {
+        println!(
+            "{}: you are benchmarking with a human player...",
+            Color::BrightYellow.paint("Warning"),
+        );
+    }
+
+    run_benchmark(
+        &args.flag_board,
+        width,
+        height,
+        colors,
+        &player,
+        args.arg_count,
+        !args.flag_no_progress,
+        !args.flag_no_threads,
+        timeout(args.flag_timeout)?,
+        args.flag_record.as_deref(),
+    )?;
+    Ok(EXIT_SUCCESS)
+}
+
+fn cmd_generate(args: GenerateArgs) -> Result<i32, Error> {
+    let (width, height, colors) = board_params(
+        args.flag_size,
+        args.flag_width,
+        args.flag_height,
+        args.flag_colors,
+    )?;
+
+    for (i, path) in args.arg_path.iter().enumerate() {
+        let id = args.flag_first_id + i as u64;
+        let board = gen_board(&args.flag_board, width, height, colors, id)?;
+
+        let extension = Path::new(path).extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("")
+            .to_lowercase();
+        match extension.as_str() {
+            "json" => {
+                File::create(path).and_then(|mut f| {
+                    writeln!(f, "{}", json::to_pretty_string(&board.to_json()))
+                }).map_err(|e| Error::io(path.as_str(), e))?;
+            }
+            "png" | "svg" | "ppm" => {
+                let options = RenderOptions::default();
+                render::save_board(path, &board, &options)?;
+            }
+            _ => {
+                File::create(path)
+                    .and_then(|mut f| board.write_text(&mut f))
+                    .map_err(|e| Error::io(path.as_str(), e))?;
+            }
+        }
+        println!("{}: {}", path, printable_id(&board));
+    }
+
+    Ok(EXIT_SUCCESS)
+}
+
+fn cmd_verify(args: VerifyArgs) -> Result<i32, Error> {
+    let (width, height, colors) = board_params(
+        args.flag_size,
+        args.flag_width,
+        args.flag_height,
+        args.flag_colors,
+    )?;
+    let board = gen_board(&args.arg_board, width, height, colors, 0)?;
+    let moves = load_solution(&args.arg_solution, board.colors())?;
+    let res = verify::verify(&board, &moves);
+
+    if !args.flag_quiet {
+        println!(" move  color  captured   owned");
+        for (i, m) in res.moves.iter().enumerate() {
+            let note = if !m.legal {
+                Color::Red.paint("illegal (color doesn't exist)")
+            } else if m.redundant {
+                Color::BrightYellow.paint("redundant")
+            } else {
+                Color::NotSet.paint("")
+            };
+            // the color in the move notation
             println!(
-                "{}: you are benchmarking with a human player...",
-                Color::BrightYellow.paint("Warning"),
+                "{:>5}  {:>5x}  {:>8}  {:>6}  {}",
+                i + 1,
+                m.color.tag,
+                m.captured,
+                m.owned,
+                note,
             );
         }
+    }
 
-        run_benchmark(
-            &args.flag_board,
-            args.flag_size,
-            &player,
-            count,
-            !args.flag_no_progress,
-            !args.flag_no_threads,
-        )
+    let illegal = res.illegal_count();
+    let redundant = res.redundant_count();
+    println!(
+        "{} moves, {} illegal, {} redundant",
+        moves.len(),
+        illegal,
+        redundant,
+    );
+    match res.drenched_after {
+        Some(n) => println!("The board is drenched after {} moves", n),
+        None => println!("The board is NOT drenched"),
+    }
+
+    let valid = res.is_valid() && !(args.flag_strict && redundant > 0);
+    if valid {
+        println!("{}", Color::BrightGreen.paint("Valid solution"));
+        Ok(EXIT_SUCCESS)
     } else {
-        play_standard_mode(
-            &args.flag_board,
-            args.flag_size,
-            &player,
-        )
-    };
+        println!("{}", Color::Red.paint("Invalid solution"));
+        Ok(EXIT_UNSOLVED)
+    }
+}
+
+/// Loads the solution given on the command line (see USAGE_VERIFY).
+fn load_solution(s: &str, colors: u8) -> Result<drencher::Solution, Error> {
+    if let Some(path) = s.strip_prefix("json:") {
+        let mut content = String::new();
+        File::open(path)
+            .and_then(|mut f| f.read_to_string(&mut content))
+            .map_err(|e| Error::io(path, e))?;
+
+        // Either a solution or a game record
+        let res = json::parse(&content).and_then(|json| {
+            if json.find("board").is_some() {
+                GameRecord::from_json(&json).map(|record| record.moves)
+            } else {
+                json::solution_from_json(&json, colors)
+            }
+        });
+        return res.map_err(|e| {
+            Error::parse(format!("solution file '{}'", path), e)
+        });
+    }
+
+    // Colors which don't exist on the board are accepted here; the verifier
+    // reports them as illegal moves.
+    notation::parse_moves(s, MAX_COLORS).map_err(|e| {
+        Error::parse("solution", e)
+    })
+}
 
-    if res.is_err() {
-        std::process::exit(1);
+fn cmd_analyze(args: AnalyzeArgs) -> Result<i32, Error> {
+    let (width, height, colors) = board_params(
+        args.flag_size,
+        args.flag_width,
+        args.flag_height,
+        args.flag_colors,
+    )?;
+    let board = gen_board(&args.arg_board, width, height, colors, 0)?;
+    let graph = RegionGraph::new(&board);
+
+    // Number of cells and regions per color
+    let mut cells = vec![0; board.colors() as usize];
+    let mut regions = vec![0; board.colors() as usize];
+    for id in graph.regions() {
+        let tag = graph.color(id).tag as usize;
+        cells[tag] += graph.size(id);
+        regions[tag] += 1;
+    }
+    let largest = graph.regions().map(|id| graph.size(id)).max().unwrap_or(0);
+    let num_cells = board.width() as usize * board.height() as usize;
+
+    // Every color of a region besides the root has to be played at least once
+    let mut missing = vec![false; board.colors() as usize];
+    for id in graph.regions().filter(|&id| id != ROOT) {
+        missing[graph.color(id).tag as usize] = true;
+    }
+    let color_bound = missing.iter().filter(|&&m| m).count();
+    let eccentricity = graph.eccentricity(ROOT);
+
+    let heuristic = Heuristic.solve(board.clone()).moves.len();
+
+    println!(
+        "Board:              {}x{}, {} colors",
+        board.width(),
+        board.height(),
+        board.colors(),
+    );
+    println!("ID:                 {}", printable_id(&board));
+    println!(
+        "Regions:            {} (largest: {} cells, average: {:.1} cells)",
+        graph.len(),
+        largest,
+        num_cells as f64 / graph.len() as f64,
+    );
+    println!("Owned cells:        {}", board.field_size());
+    println!(
+        "Adjacent colors:    {}",
+        notation::format_moves(&board.adjacent_colors()),
+    );
+    println!("Colors:");
+    for tag in 0..board.colors() {
+        println!(
+            "  {} {:>2}: {:>6} cells in {:>5} regions",
+            drencher::Color::new(tag),
+            tag,
+            cells[tag as usize],
+            regions[tag as usize],
+        );
     }
+    println!(
+        "Lower bounds:       {} (colors not owned), {} (eccentricity)",
+        color_bound,
+        eccentricity,
+    );
+    println!("Heuristic solution: {} moves", heuristic);
+
+    Ok(EXIT_SUCCESS)
 }
 
-fn play_standard_mode(init_algo: &str, size: u8, player: &str)
-    -> Result<(), ()>
-{
+#[allow(clippy::too_many_arguments)]
+fn play_standard_mode(
+    init_algo: &str,
+    width: u16,
+    height: u16,
+    colors: u8,
+    player: &str,
+    save_path: Option<&str>,
+    record_path: Option<&str>,
+    render_path: Option<&str>,
+    replay_path: Option<&str>,
+    replay_delay: u32,
+) -> Result<(), Error> {
     println!("~~~~~~ Playing a standard game ~~~~~~");
 
     // generate board and get player
-    let board = try!(gen_board(init_algo, size, 0));
-    let player = try!(get_player(player));
+    let board = gen_board(init_algo, width, height, colors, 0)?;
+    let player_name = player;
+    let player = get_player(player)?;
+
+    // save the initial board if requested
+    if let Some(path) = save_path {
+        let res = File::create(path).and_then(|mut f| board.write_text(&mut f));
+        res.map_err(|e| Error::io(path, e))?;
+    }
+
+    println!("Board ID: {}", printable_id(&board));
 
     // let the player try to solve the board
-    let res = player.solve(board.clone());
+    let outcome = player.solve(board.clone());
+    let moves = &outcome.moves;
 
     // depending on whether the player already prints output
     if !player.prints_output() {
         // go through all the moves and print the board at every state
-        let mut board = board;
+        let mut board = board.clone();
         println!("Start board:\n{}", board);
-        for &c in res.as_ref().unwrap_or_else(|e| e) {
+        for &c in moves {
             println!("Drenching: {}", c);
             board.drench(c);
             println!("{}", board);
         }
-        println!("");
+        println!();
     }
 
-    match res {
-        Ok(res) => println!("Game was solved (in {} steps)! :-)", res.len()),
-        Err(_) => println!("Game was NOT solved! :-("),
+    match outcome.failure {
+        None => {
+            println!(
+                "Game was solved (in {} steps{})! :-)",
+                moves.len(),
+                if outcome.optimal { ", optimal" } else { "" },
+            )
+        }
+        Some(reason) => println!("Game was NOT solved ({})! :-(", reason),
+    }
+    println!("Moves: {}", notation::format_moves(moves));
+    print_search_stats(&outcome);
+
+    // render the game if requested
+    if let Some(path) = render_path {
+        let options = RenderOptions {
+            coordinates: true,
+            highlight_owned: true,
+            .. RenderOptions::default()
+        };
+        render::save_solution(path, &board, moves, &options)?;
+    }
+
+    // save the animation if requested
+    if let Some(path) = replay_path {
+        let options = AnimationOptions {
+            delay: replay_delay,
+            .. AnimationOptions::default()
+        };
+        animation::save_replay(path, &board, moves, &options)?;
+    }
+
+    // save the game record if requested
+    if let Some(path) = record_path {
+        save_record(path, &GameRecord {
+            board: board,
+            generator: init_algo.to_string(),
+            seed: generator_seed(init_algo, 0),
+            player: player_name.to_string(),
+            moves: moves.clone(),
+            solved: outcome.is_solved(),
+        })?;
     }
 
     Ok(())
 }
 
-fn gen_board(init_algo: &str, size: u8, id: u64) -> Result<Board, ()> {
-    match init_algo {
-        "random" => Ok(Board::random(size)),
-        "deter0" => Ok(Board::deterministic_random(size, id)),
-        "uniform" => Ok(Board::uniform(size)),
-        "permutations" => Ok(Board::permutation(size, id)),
-        other => {
-            println!("Intial board algorithm '{}' doesn't exist!", other);
-            Err(())
+/// Converts a `--timeout` in seconds into a duration. Negative, infinite and
+/// NaN timeouts are invalid.
+fn timeout(secs: Option<f64>) -> Result<Option<Duration>, Error> {
+    match secs {
+        None => Ok(None),
+        Some(secs) if secs >= 0.0 && secs.is_finite() => {
+            Ok(Some(Duration::milliseconds((secs * 1000.0) as i64)))
         }
+        Some(secs) => Err(Error::parse(
+            "timeout",
+            format!("'{}' is not a non-negative number of seconds", secs),
+        )),
     }
 }
 
-fn get_player(name: &str) -> Result<Box<Solver>, ()> {
-    match name {
-        "human" => Ok(Box::new(solver::Human)),
-        "exact" => Ok(Box::new(solver::Exact)),
-        "random" => Ok(Box::new(solver::Random)),
-        "heuristic" => Ok(Box::new(solver::Heuristic)),
-        "modcount" => Ok(Box::new(solver::ModCount)),
-        other => {
-            println!("Player '{}' does not exist!", other);
-            Err(())
-        }
+/// Prints the search statistics of the outcome, if the solver collected
+/// any.
+fn print_search_stats(outcome: &SolveOutcome) {
+    if outcome.nodes_expanded > 0 {
+        println!(
+            "Nodes expanded: {}, peak frontier: {}",
+            outcome.nodes_expanded,
+            outcome.peak_frontier,
+        );
+    }
+    if let Some(initial) = outcome.initial_bound {
+        println!(
+            "Initial bound: {} moves, improved by {}",
+            initial,
+            initial.saturating_sub(outcome.moves.len()),
+        );
     }
 }
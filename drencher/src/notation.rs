@@ -0,0 +1,76 @@
+//! Compact move notation for solutions.
+//!
+//! Every move is written as one character: the color tag as (hexadecimal)
+//! digit, just like the cells in the `text` board format. So `0`-`9` are the
+//! first ten colors and `a`-`f` (or `A`-`F`) the remaining ones. A solution
+//! for a standard board might look like `2504132051342`. Whitespace between
+//! moves is ignored when parsing, which allows grouping long solutions.
+//!
+//! Formatting and parsing round-trips for every number of colors up to
+//! `MAX_COLORS`.
+
+use color::{Color, MAX_COLORS};
+use solver::Solution;
+use std::error::Error;
+use std::fmt;
+
+
+/// Error returned when parsing an invalid move sequence. The position is the
+/// 1-based index of the offending character.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseMovesError {
+    pub position: usize,
+    pub msg: String,
+}
+
+impl fmt::Display for ParseMovesError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "character {}: {}", self.position, self.msg)
+    }
+}
+
+impl Error for ParseMovesError {
+    fn description(&self) -> &str {
+        &self.msg
+    }
+}
+
+/// Returns the moves in the compact notation.
+pub fn format_moves(moves: &[Color]) -> String {
+    moves.iter().map(|c| format!("{:x}", c.tag)).collect()
+}
+
+/// Parses moves in the compact notation. All moves have to be colors of a
+/// board with `colors` colors.
+pub fn parse_moves(s: &str, colors: u8) -> Result<Solution, ParseMovesError> {
+    assert!((1..=MAX_COLORS).contains(&colors), "invalid number of colors");
+
+    let mut moves = Vec::with_capacity(s.len());
+    for (i, c) in s.chars().enumerate() {
+        if c.is_whitespace() {
+            continue;
+        }
+
+        match c.to_digit(16) {
+            Some(tag) if (tag as u8) < colors => {
+                moves.push(Color::new(tag as u8));
+            }
+            Some(_) => return Err(ParseMovesError {
+                position: i + 1,
+                msg: format!(
+                    "color {} doesn't exist on a board with {} colors",
+                    c,
+                    colors,
+                ),
+            }),
+            None => return Err(ParseMovesError {
+                position: i + 1,
+                msg: format!(
+                    "invalid move '{}' (expected a digit or a letter a-f)",
+                    c,
+                ),
+            }),
+        }
+    }
+    Ok(moves)
+}
@@ -0,0 +1,505 @@
+//! Rendering boards to PNG and SVG images.
+//!
+//! In contrast to the `Display` implementation of `Board` and the `human`
+//! player, this doesn't need a terminal or an OpenGL window, so it can be
+//! used for reports and the like. A single board or a strip of all states
+//! along a solution (the initial board and the board after every move, from
+//! left to right) can be rendered. The colors of the cells are the ones from
+//! `Color::as_rgb`.
+//!
+//! Both output formats are drawn by the same code: the layout is described
+//! once with the `Canvas` operations and executed either on a pixel buffer
+//! or as SVG elements.
+//!
+//! Raster images are limited to `MAX_PIXELS` pixels. Larger boards, cells
+//! or strips result in an `Error::InvalidSize` instead of an allocation of
+//! several gigabytes.
+
+use board::{Board, Pos};
+use color::Color;
+use error::Error;
+use image::{self, Image, Rgb};
+use std::cmp;
+use std::fmt::Write as FmtWrite;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use std::slice;
+
+
+const BACKGROUND: Rgb = [255, 255, 255];
+const GRID: Rgb = [40, 40, 40];
+const HIGHLIGHT: Rgb = [0, 0, 0];
+const TEXT: Rgb = [0, 0, 0];
+
+/// The maximum number of pixels of a raster image (64 megapixels, which
+/// need about 200 MB of memory).
+pub const MAX_PIXELS: u64 = 1 << 26;
+
+/// Options for rendering boards.
+#[derive(Clone, Debug)]
+pub struct RenderOptions {
+    /// Side length of one cell in pixels.
+    pub cell_size: u32,
+    /// Draw lines between the cells.
+    pub grid: bool,
+    /// Label the columns and rows with their (0-based) index.
+    pub coordinates: bool,
+    /// Draw an outline around the cells owned by the player.
+    pub highlight_owned: bool,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        RenderOptions {
+            cell_size: 16,
+            grid: true,
+            coordinates: false,
+            highlight_owned: false,
+        }
+    }
+}
+
+/// Renders the board as raster image.
+pub fn render_board(b: &Board, options: &RenderOptions)
+    -> Result<Image, Error>
+{
+    let mut canvas = Raster::new(Layout::new(b, 1, false, options)?)?;
+    draw(&mut canvas, slice::from_ref(b), &[], options);
+    Ok(canvas.into_image())
+}
+
+/// Renders the board as raster image with the given caption centered below
+/// it. The caption can only contain digits, spaces and `/`.
+pub fn render_captioned(b: &Board, caption: &str, options: &RenderOptions)
+    -> Result<Image, Error>
+{
+    let mut canvas = Raster::new(Layout::new(b, 1, true, options)?)?;
+    draw(&mut canvas, slice::from_ref(b), &[caption.to_string()], options);
+    Ok(canvas.into_image())
+}
+
+/// Renders the initial board and the board after every move side by side as
+/// raster image.
+pub fn render_solution(b: &Board, moves: &[Color], options: &RenderOptions)
+    -> Result<Image, Error>
+{
+    let layout = Layout::new(b, moves.len() + 1, false, options)?;
+    let mut canvas = Raster::new(layout)?;
+    draw(&mut canvas, &states(b, moves), &[], options);
+    Ok(canvas.into_image())
+}
+
+/// Renders the board as SVG document.
+pub fn board_to_svg(b: &Board, options: &RenderOptions)
+    -> Result<String, Error>
+{
+    let mut canvas = Svg::new(Layout::new(b, 1, false, options)?);
+    draw(&mut canvas, slice::from_ref(b), &[], options);
+    Ok(canvas.finish())
+}
+
+/// Renders the initial board and the board after every move side by side as
+/// SVG document.
+pub fn solution_to_svg(b: &Board, moves: &[Color], options: &RenderOptions)
+    -> Result<String, Error>
+{
+    let layout = Layout::new(b, moves.len() + 1, false, options)?;
+    let mut canvas = Svg::new(layout);
+    draw(&mut canvas, &states(b, moves), &[], options);
+    Ok(canvas.finish())
+}
+
+/// Renders the board to the given file. The format is determined by the
+/// extension: `.svg`, `.png` or `.ppm`.
+pub fn save_board<P: AsRef<Path>>(
+    path: P,
+    b: &Board,
+    options: &RenderOptions,
+) -> Result<(), Error> {
+    save_solution(path, b, &[], options)
+}
+
+/// Renders the strip of board states along the solution to the given file.
+/// The format is determined by the extension: `.svg`, `.png` or `.ppm`.
+pub fn save_solution<P: AsRef<Path>>(
+    path: P,
+    b: &Board,
+    moves: &[Color],
+    options: &RenderOptions,
+) -> Result<(), Error> {
+    let path = path.as_ref();
+    let is_svg = path.extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase() == "svg")
+        .unwrap_or(false);
+
+    if is_svg {
+        let svg = solution_to_svg(b, moves, options)?;
+        File::create(path)
+            .and_then(|mut f| f.write_all(svg.as_bytes()))
+            .map_err(|e| Error::io(path.display().to_string(), e))
+    } else {
+        render_solution(b, moves, options)?
+            .save(path)
+            .map_err(|e| Error::io(path.display().to_string(), e))
+    }
+}
+
+/// Returns the initial board and the board after every move.
+fn states(b: &Board, moves: &[Color]) -> Vec<Board> {
+    let mut states = vec![b.clone()];
+    let mut board = b.clone();
+    for &m in moves {
+        board.drench(m);
+        states.push(board.clone());
+    }
+    states
+}
+
+/// Positions of all elements in the image, in pixels.
+struct Layout {
+    width: u32,
+    height: u32,
+    cell_size: u32,
+    // scale factor of the font
+    font_scale: u32,
+    // position of the top left corner of the first board
+    origin: (u32, u32),
+    // horizontal distance between the boards of a strip
+    spacing: u32,
+}
+
+impl Layout {
+    /// Returns an error if the width or height doesn't fit into an `u32`.
+    fn new(b: &Board, frames: usize, captioned: bool, options: &RenderOptions)
+        -> Result<Self, Error>
+    {
+        let cell_size = cmp::max(options.cell_size, 1);
+        let font_scale = cmp::max(cell_size / 8, 1);
+
+        // Space for the row labels on the left and the column labels on top
+        let origin = if options.coordinates {
+            let digits = (b.height() - 1).to_string().len() as u32;
+            (
+                digits * GLYPH_ADVANCE * font_scale + 2 * font_scale,
+                GLYPH_HEIGHT * font_scale + 2 * font_scale,
+            )
+        } else {
+            (0, 0)
+        };
+
+        // Computed with 64 bits, because big boards and cells overflow an
+        // `u32`. Only the number of frames is unbounded.
+        let board_width = b.width() as u64 * cell_size as u64;
+        let board_height = b.height() as u64 * cell_size as u64;
+        let spacing = cell_size;
+        let frames = frames as u64;
+        let caption_height = if captioned {
+            (GLYPH_HEIGHT + 2) as u64 * font_scale as u64
+        } else {
+            0
+        };
+
+        let width = (board_width + spacing as u64)
+            .saturating_mul(frames)
+            .saturating_add(origin.0 as u64)
+            - spacing as u64;
+        let height = origin.1 as u64 + board_height + caption_height;
+        let max = ::std::u32::MAX as u64;
+        if width > max || height > max {
+            return Err(Error::InvalidSize(format!(
+                "the image would be too big ({}x{} pixels)",
+                width,
+                height,
+            )));
+        }
+
+        Ok(Layout {
+            width: width as u32,
+            height: height as u32,
+            cell_size: cell_size,
+            font_scale: font_scale,
+            origin: origin,
+            spacing: spacing,
+        })
+    }
+}
+
+/// Drawing operations used to render boards.
+trait Canvas {
+    fn layout(&self) -> &Layout;
+
+    /// Fills the given rectangle.
+    fn rect(&mut self, x: u32, y: u32, w: u32, h: u32, color: Rgb);
+
+    /// Writes the text with its top left corner at the given position. Only
+    /// digits, spaces and `/` are supported.
+    fn text(&mut self, x: u32, y: u32, text: &str, color: Rgb);
+}
+
+/// Draws all given boards side by side. If given, the i-th caption is drawn
+/// below the i-th board.
+fn draw<C: Canvas>(
+    canvas: &mut C,
+    states: &[Board],
+    captions: &[String],
+    options: &RenderOptions,
+) {
+    let (width, height) = (canvas.layout().width, canvas.layout().height);
+    canvas.rect(0, 0, width, height, BACKGROUND);
+
+    let cell = canvas.layout().cell_size;
+    let (origin_x, origin_y) = canvas.layout().origin;
+    let scale = canvas.layout().font_scale;
+    let spacing = canvas.layout().spacing;
+
+    for (i, b) in states.iter().enumerate() {
+        let left = origin_x + i as u32 * (b.width() as u32 * cell + spacing);
+        let top = origin_y;
+        let cell_pos = |(x, y): Pos| {
+            (left + x as u32 * cell, top + y as u32 * cell)
+        };
+
+        for y in 0..b.height() {
+            for x in 0..b.width() {
+                let (px, py) = cell_pos((x, y));
+                canvas.rect(px, py, cell, cell, image::to_rgb(b[(x, y)]));
+            }
+        }
+
+        if options.grid && cell > 2 {
+            let board_width = b.width() as u32 * cell;
+            let board_height = b.height() as u32 * cell;
+            for x in 0..b.width() as u32 {
+                canvas.rect(left + x * cell, top, 1, board_height, GRID);
+            }
+            for y in 0..b.height() as u32 {
+                canvas.rect(left, top + y * cell, board_width, 1, GRID);
+            }
+            canvas.rect(left + board_width - 1, top, 1, board_height, GRID);
+            canvas.rect(left, top + board_height - 1, board_width, 1, GRID);
+        }
+
+        if options.highlight_owned {
+            // Draw a line on every side of an owned cell that doesn't touch
+            // another owned cell.
+            let t = cmp::min(cmp::max(cell / 8, 2), cell);
+            for y in 0..b.height() {
+                for x in 0..b.width() {
+                    if !b.is_owned((x, y)) {
+                        continue;
+                    }
+
+                    let (px, py) = cell_pos((x, y));
+                    if x == 0 || !b.is_owned((x - 1, y)) {
+                        canvas.rect(px, py, t, cell, HIGHLIGHT);
+                    }
+                    if x == b.width() - 1 || !b.is_owned((x + 1, y)) {
+                        canvas.rect(px + cell - t, py, t, cell, HIGHLIGHT);
+                    }
+                    if y == 0 || !b.is_owned((x, y - 1)) {
+                        canvas.rect(px, py, cell, t, HIGHLIGHT);
+                    }
+                    if y == b.height() - 1 || !b.is_owned((x, y + 1)) {
+                        canvas.rect(px, py + cell - t, cell, t, HIGHLIGHT);
+                    }
+                }
+            }
+        }
+
+        if options.coordinates {
+            for x in 0..b.width() {
+                let label = x.to_string();
+                let w = label.len() as u32 * GLYPH_ADVANCE * scale;
+                let (px, _) = cell_pos((x, 0));
+                let px = (px + cell / 2).saturating_sub(w / 2);
+                canvas.text(px, scale, &label, TEXT);
+            }
+
+            // Row labels only on the left of the first board
+            if i == 0 {
+                for y in 0..b.height() {
+                    let label = y.to_string();
+                    let w = label.len() as u32 * GLYPH_ADVANCE * scale;
+                    let (_, py) = cell_pos((0, y));
+                    let py = (py + cell / 2)
+                        .saturating_sub(GLYPH_HEIGHT * scale / 2);
+                    canvas.text(origin_x - w - scale, py, &label, TEXT);
+                }
+            }
+        }
+
+        if let Some(caption) = captions.get(i) {
+            let w = caption.len() as u32 * GLYPH_ADVANCE * scale;
+            let center = left + b.width() as u32 * cell / 2;
+            let y = top + b.height() as u32 * cell + scale;
+            canvas.text(center.saturating_sub(w / 2), y, caption, TEXT);
+        }
+    }
+}
+
+
+// ===========================================================================
+// Raster output
+// ===========================================================================
+
+// A tiny 3x5 pixel font for the digits and `/`. Every row of a glyph is a 3
+// bit number, the most significant bit being the leftmost pixel.
+const GLYPH_WIDTH: u32 = 3;
+const GLYPH_HEIGHT: u32 = 5;
+const GLYPH_ADVANCE: u32 = GLYPH_WIDTH + 1;
+const DIGITS: [[u8; 5]; 10] = [
+    [7, 5, 5, 5, 7],
+    [2, 6, 2, 2, 7],
+    [7, 1, 7, 4, 7],
+    [7, 1, 7, 1, 7],
+    [5, 5, 7, 1, 1],
+    [7, 4, 7, 1, 7],
+    [7, 4, 7, 5, 7],
+    [7, 1, 1, 1, 1],
+    [7, 5, 7, 5, 7],
+    [7, 5, 7, 1, 7],
+];
+const SLASH: [u8; 5] = [1, 1, 2, 4, 4];
+
+struct Raster {
+    layout: Layout,
+    pixels: Vec<Rgb>,
+}
+
+impl Raster {
+    /// Returns an error instead of allocating more than `MAX_PIXELS` pixels.
+    fn new(layout: Layout) -> Result<Self, Error> {
+        let len = layout.width as u64 * layout.height as u64;
+        if len > MAX_PIXELS {
+            return Err(Error::InvalidSize(format!(
+                "the image would have {} pixels ({}x{}), but at most {} are \
+                 allowed",
+                len,
+                layout.width,
+                layout.height,
+                MAX_PIXELS,
+            )));
+        }
+
+        Ok(Raster {
+            layout: layout,
+            pixels: vec![BACKGROUND; len as usize],
+        })
+    }
+
+    fn into_image(self) -> Image {
+        Image::new(self.layout.width, self.layout.height, self.pixels)
+    }
+}
+
+impl Canvas for Raster {
+    fn layout(&self) -> &Layout {
+        &self.layout
+    }
+
+    fn rect(&mut self, x: u32, y: u32, w: u32, h: u32, color: Rgb) {
+        let x_end = cmp::min(x + w, self.layout.width);
+        let y_end = cmp::min(y + h, self.layout.height);
+        for py in y..y_end {
+            let row = py as usize * self.layout.width as usize;
+            for px in x..x_end {
+                self.pixels[row + px as usize] = color;
+            }
+        }
+    }
+
+    fn text(&mut self, x: u32, y: u32, text: &str, color: Rgb) {
+        let scale = self.layout.font_scale;
+        for (i, c) in text.chars().enumerate() {
+            let glyph = match c {
+                '0'..='9' => DIGITS[c as usize - '0' as usize],
+                '/' => SLASH,
+                _ => continue,
+            };
+            let left = x + i as u32 * GLYPH_ADVANCE * scale;
+            for (row, bits) in glyph.iter().enumerate() {
+                for col in 0..GLYPH_WIDTH {
+                    if bits & (1 << (GLYPH_WIDTH - 1 - col)) != 0 {
+                        self.rect(
+                            left + col * scale,
+                            y + row as u32 * scale,
+                            scale,
+                            scale,
+                            color,
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
+
+
+// ===========================================================================
+// SVG output
+// ===========================================================================
+
+struct Svg {
+    layout: Layout,
+    out: String,
+}
+
+impl Svg {
+    fn new(layout: Layout) -> Self {
+        let out = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" \
+             height=\"{1}\" viewBox=\"0 0 {0} {1}\" \
+             shape-rendering=\"crispEdges\">\n",
+            layout.width,
+            layout.height,
+        );
+        Svg {
+            layout: layout,
+            out: out,
+        }
+    }
+
+    fn finish(mut self) -> String {
+        self.out.push_str("</svg>\n");
+        self.out
+    }
+}
+
+fn hex(c: Rgb) -> String {
+    format!("#{:02x}{:02x}{:02x}", c[0], c[1], c[2])
+}
+
+impl Canvas for Svg {
+    fn layout(&self) -> &Layout {
+        &self.layout
+    }
+
+    fn rect(&mut self, x: u32, y: u32, w: u32, h: u32, color: Rgb) {
+        writeln!(
+            self.out,
+            "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"/>",
+            x,
+            y,
+            w,
+            h,
+            hex(color),
+        ).unwrap();
+    }
+
+    fn text(&mut self, x: u32, y: u32, text: &str, color: Rgb) {
+        // The font size is chosen to roughly match the raster font.
+        let scale = self.layout.font_scale;
+        writeln!(
+            self.out,
+            "<text x=\"{}\" y=\"{}\" font-family=\"monospace\" \
+             font-size=\"{}\" fill=\"{}\">{}</text>",
+            x,
+            y + GLYPH_HEIGHT * scale,
+            (GLYPH_HEIGHT + 2) * scale,
+            hex(color),
+            text,
+        ).unwrap();
+    }
+}
@@ -0,0 +1,220 @@
+//! A* Solver
+//!
+//! Like the `Exact` solver, this solver always finds an optimal solution, but
+//! it searches the game tree best-first instead of level by level: the state
+//! with the smallest number of moves plus lower bound for the remaining moves
+//! (see `StateSpace::lower_bound`) is expanded next. As the bound never
+//! overestimates, the first solution found is optimal, and states which
+//! can't lead to an optimal solution are never expanded. States owning the
+//! same regions are only expanded once.
+//!
+//! The progress reports use the current estimate of the solution length as
+//! depth. Boards with more than `MAX_NODES` islands are rejected.
+
+use board::Board;
+use color::Color;
+use graph::RegionGraph;
+use super::{CancelToken, FailureReason, Solution, Solver, SolveOutcome};
+use super::Progress;
+use super::node_set::{NodeSet, MAX_NODES};
+use super::state_space::{Node, StateSpace};
+use std::cmp::{self, Ordering};
+use std::collections::{BinaryHeap, HashMap};
+use std::collections::hash_map::Entry as MapEntry;
+use time::SteadyTime;
+
+/// Type definition of the A* solver. See module documentation for more
+/// information.
+pub struct AStar;
+
+/// The cancel token is checked every time this many states were expanded.
+const CANCEL_CHECK_INTERVAL: u64 = 1024;
+
+/// A generated state together with the move that led to it. The moves of a
+/// state are found by following the parents up to the root.
+struct Entry<S> {
+    node: Node<S>,
+    parent: usize,
+    color: Color,
+}
+
+/// An item of the open list, referring to an `Entry`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct Open {
+    /// Number of moves plus lower bound for the remaining moves
+    estimate: usize,
+    /// Number of moves
+    depth: usize,
+    index: usize,
+}
+
+impl Ord for Open {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // The heap pops the greatest item first, so the smallest estimate
+        // has to be the greatest. Ties are broken in favor of deeper states
+        // (closer to a solution) and then of older ones.
+        (other.estimate, self.depth, other.index)
+            .cmp(&(self.estimate, other.depth, self.index))
+    }
+}
+
+impl PartialOrd for Open {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Solver for AStar {
+    fn solve_with_progress(
+        &self,
+        b: Board,
+        cancel: &CancelToken,
+        progress: &mut dyn FnMut(&Progress),
+    ) -> SolveOutcome {
+        if b.is_drenched() {
+            return SolveOutcome {
+                optimal: true,
+                .. SolveOutcome::solved(vec![])
+            };
+        }
+
+        let g = RegionGraph::new(&b);
+        let outcome =
+            with_node_set!(g.len(), search(&b, &g, cancel, progress));
+        outcome.unwrap_or_else(|| {
+            info!(
+                "the A* solver can't handle this board: it has {} islands, \
+                 but at most {} are supported",
+                g.len(),
+                MAX_NODES,
+            );
+            SolveOutcome::failed(vec![], FailureReason::Unsupported)
+        })
+    }
+}
+
+/// The actual best-first search, working with sets of type `S`.
+fn search<S: NodeSet>(
+    b: &Board,
+    g: &RegionGraph,
+    cancel: &CancelToken,
+    progress: &mut dyn FnMut(&Progress),
+) -> SolveOutcome {
+    let space = StateSpace::<S>::new(b, g);
+    let start = SteadyTime::now();
+
+    let root = space.root();
+    let mut entries = vec![Entry {
+        node: root,
+        parent: 0,
+        color: Color::new(0),
+    }];
+    let mut open = BinaryHeap::new();
+    open.push(Open {
+        estimate: space.lower_bound(&root),
+        depth: 0,
+        index: 0,
+    });
+
+    // The smallest number of moves each known state was reached with
+    let mut depths = HashMap::new();
+    depths.insert(root.owned, 0);
+
+    let mut nodes_expanded = 0;
+    let mut peak_frontier = 1;
+    let mut pruned = 0;
+    let mut bound = 0;
+    // The expanded entry owning the most regions, the best partial solution
+    let mut best = 0;
+
+    while let Some(Open { estimate, depth, index }) = open.pop() {
+        if nodes_expanded % CANCEL_CHECK_INTERVAL == 0
+            && cancel.is_cancelled()
+        {
+            return SolveOutcome {
+                nodes_expanded: nodes_expanded,
+                peak_frontier: peak_frontier,
+                .. SolveOutcome::failed(
+                    moves_to(&entries, best),
+                    FailureReason::Aborted,
+                )
+            };
+        }
+
+        // The state was reached with fewer moves after this item was pushed
+        let node = entries[index].node;
+        if depths[&node.owned] < depth {
+            continue;
+        }
+
+        // As the bound never overestimates (and doesn't decrease by more
+        // than one per move), the first solution we pop is optimal.
+        if node.is_goal() {
+            return SolveOutcome {
+                optimal: true,
+                nodes_expanded: nodes_expanded,
+                peak_frontier: peak_frontier,
+                .. SolveOutcome::solved(moves_to(&entries, index))
+            };
+        }
+
+        if estimate > bound {
+            bound = estimate;
+            debug!("estimate {} with {} open states", bound, open.len());
+            progress(&Progress {
+                depth: bound,
+                frontier: open.len(),
+                pruned: pruned,
+                elapsed: SteadyTime::now() - start,
+            });
+        }
+
+        nodes_expanded += 1;
+        if node.owned.len() > entries[best].node.owned.len() {
+            best = index;
+        }
+
+        for (color, child) in space.children(&node) {
+            // Only keep the child if it wasn't reached with as few moves yet
+            match depths.entry(child.owned) {
+                MapEntry::Occupied(ref e) if *e.get() <= depth + 1 => {
+                    pruned += 1;
+                    continue;
+                }
+                MapEntry::Occupied(mut e) => {
+                    e.insert(depth + 1);
+                }
+                MapEntry::Vacant(e) => {
+                    e.insert(depth + 1);
+                }
+            }
+
+            entries.push(Entry {
+                node: child,
+                parent: index,
+                color: color,
+            });
+            open.push(Open {
+                estimate: depth + 1 + space.lower_bound(&child),
+                depth: depth + 1,
+                index: entries.len() - 1,
+            });
+        }
+        peak_frontier = cmp::max(peak_frontier, open.len());
+    }
+
+    // Every board can be drenched, so the search always finds a solution
+    unreachable!();
+}
+
+/// Returns the moves leading from the root to the entry with the given
+/// index.
+fn moves_to<S>(entries: &[Entry<S>], mut index: usize) -> Solution {
+    let mut moves = Vec::new();
+    while index != 0 {
+        moves.push(entries[index].color);
+        index = entries[index].parent;
+    }
+    moves.reverse();
+    moves
+}
@@ -0,0 +1,205 @@
+//! Branch and Bound Solver
+//!
+//! Optimal solver which starts with the solution of the `Heuristic` solver
+//! (the *incumbent*) and then searches the game tree depth-first for shorter
+//! ones. A branch is cut off as soon as its number of moves plus lower bound
+//! (see `StateSpace::lower_bound`) can't beat the incumbent, and every
+//! shorter solution found becomes the new incumbent. Once the whole tree is
+//! searched, the incumbent is optimal. The outcome contains the length of
+//! the heuristic solution as `initial_bound`, to see how much it was
+//! improved.
+//!
+//! If the solver is cancelled, it fails with `Aborted`, but still returns the
+//! incumbent (or the partial heuristic solution, if it's cancelled that
+//! early). The progress reports use the length of the incumbent as depth.
+
+use board::Board;
+use graph::RegionGraph;
+use super::{CancelToken, FailureReason, Solution, Solver, SolveOutcome};
+use super::{Heuristic, Progress};
+use super::node_set::{NodeSet, MAX_NODES};
+use super::state_space::{Node, StateSpace, Table};
+use std::cmp;
+use time::SteadyTime;
+
+/// Type definition of the branch and bound solver. See module documentation
+/// for more information.
+pub struct BranchAndBound;
+
+/// Memory used by the transposition table: 32 MiB.
+const TABLE_BYTES: usize = 32 * 1024 * 1024;
+
+/// The cancel token is checked every time this many states were expanded.
+const CANCEL_CHECK_INTERVAL: u64 = 1024;
+
+/// Progress is reported every time this many states were expanded (and every
+/// time the incumbent is improved).
+const PROGRESS_INTERVAL: u64 = 1 << 16;
+
+impl Solver for BranchAndBound {
+    fn solve_with_progress(
+        &self,
+        b: Board,
+        cancel: &CancelToken,
+        progress: &mut dyn FnMut(&Progress),
+    ) -> SolveOutcome {
+        if b.is_drenched() {
+            return SolveOutcome {
+                optimal: true,
+                .. SolveOutcome::solved(vec![])
+            };
+        }
+
+        let g = RegionGraph::new(&b);
+        let outcome =
+            with_node_set!(g.len(), search(&b, &g, cancel, progress));
+        outcome.unwrap_or_else(|| {
+            info!(
+                "the branch and bound solver can't handle this board: it has \
+                 {} islands, but at most {} are supported",
+                g.len(),
+                MAX_NODES,
+            );
+            SolveOutcome::failed(vec![], FailureReason::Unsupported)
+        })
+    }
+}
+
+/// Runs the depth-first search below the incumbent, working with sets of
+/// type `S`.
+fn search<S: NodeSet>(
+    b: &Board,
+    g: &RegionGraph,
+    cancel: &CancelToken,
+    progress: &mut dyn FnMut(&Progress),
+) -> SolveOutcome {
+    // Without a complete heuristic solution there's nothing to improve
+    let heuristic = Heuristic.solve_until(b.clone(), cancel);
+    if !heuristic.is_solved() {
+        return heuristic;
+    }
+    let incumbent = heuristic.moves;
+    let initial_bound = incumbent.len();
+    debug!("heuristic solution has {} moves", initial_bound);
+
+    let space = StateSpace::<S>::new(b, g);
+    let root = space.root();
+    let mut dfs = Search {
+        space: space,
+        table: Table::new(TABLE_BYTES),
+        cancel: cancel,
+        progress: progress,
+        start: SteadyTime::now(),
+        path: Solution::new(),
+        incumbent: incumbent,
+        nodes_expanded: 0,
+        held: 0,
+        peak_frontier: 0,
+        pruned: 0,
+    };
+    dfs.report();
+    let finished = dfs.visit(root);
+
+    SolveOutcome {
+        optimal: finished,
+        failure: if finished { None } else { Some(FailureReason::Aborted) },
+        nodes_expanded: dfs.nodes_expanded,
+        peak_frontier: dfs.peak_frontier,
+        initial_bound: Some(initial_bound),
+        .. SolveOutcome::solved(dfs.incumbent)
+    }
+}
+
+/// State of one run of the solver.
+struct Search<'a, S> {
+    space: StateSpace<S>,
+    /// The smallest number of moves each state was reached with
+    table: Table<S>,
+    cancel: &'a CancelToken,
+    progress: &'a mut dyn FnMut(&Progress),
+    start: SteadyTime,
+    /// Moves leading to the current state
+    path: Solution,
+    /// The best solution found so far
+    incumbent: Solution,
+    // Statistics for the outcome and the progress reports
+    nodes_expanded: u64,
+    held: usize,
+    peak_frontier: usize,
+    pruned: u64,
+}
+
+impl<'a, S: NodeSet> Search<'a, S> {
+    /// Searches for solutions shorter than the incumbent below `node`
+    /// (reached with the moves in `self.path`). Returns false if the search
+    /// was cancelled.
+    fn visit(&mut self, node: Node<S>) -> bool {
+        if node.is_goal() {
+            // The bound guarantees that this solution is shorter
+            debug!("found solution with {} moves", self.path.len());
+            self.incumbent = self.path.clone();
+            self.report();
+            return true;
+        }
+
+        // If this state was already reached with at most as many moves, its
+        // subtree was searched with a bound at least as loose as ours.
+        let depth = self.path.len();
+        if self.table.get(&node.owned).is_some_and(|d| d <= depth) {
+            self.pruned += 1;
+            return true;
+        }
+        self.table.insert(node.owned, depth);
+
+        if self.nodes_expanded.is_multiple_of(CANCEL_CHECK_INTERVAL)
+            && self.cancel.is_cancelled()
+        {
+            return false;
+        }
+        self.nodes_expanded += 1;
+        if self.nodes_expanded.is_multiple_of(PROGRESS_INTERVAL) {
+            self.report();
+        }
+
+        // Order the children by their lower bound, so that good solutions
+        // are found early. Whether a child can beat the incumbent is checked
+        // right before visiting it, as the incumbent might have improved in
+        // the meantime.
+        let mut children: Vec<_> = self.space.children(&node)
+            .into_iter()
+            .map(|(color, child)| {
+                (depth + 1 + self.space.lower_bound(&child), color, child)
+            })
+            .collect();
+        children.sort_by_key(|&(estimate, _, _)| estimate);
+
+        self.held += children.len();
+        self.peak_frontier = cmp::max(self.peak_frontier, self.held);
+
+        for &(estimate, color, child) in &children {
+            if estimate >= self.incumbent.len() {
+                self.pruned += 1;
+                continue;
+            }
+
+            self.path.push(color);
+            let finished = self.visit(child);
+            self.path.pop();
+            if !finished {
+                return false;
+            }
+        }
+
+        self.held -= children.len();
+        true
+    }
+
+    fn report(&mut self) {
+        (self.progress)(&Progress {
+            depth: self.incumbent.len(),
+            frontier: self.held,
+            pruned: self.pruned,
+            elapsed: SteadyTime::now() - self.start,
+        });
+    }
+}
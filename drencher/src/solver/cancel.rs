@@ -0,0 +1,55 @@
+//! Stopping solvers early.
+//!
+//! A `CancelToken` is handed to `Solver::solve_until`. Long running solvers
+//! check it every now and then and stop as soon as it's cancelled, either
+//! explicitly via `cancel()` (possibly from another thread) or because its
+//! deadline has passed. A stopped solver returns the best partial solution
+//! it has found so far, marked as aborted.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use time::{Duration, SteadyTime};
+
+
+/// Cancellation flag with an optional deadline. Clones share the flag, so
+/// cancelling one clone cancels all of them.
+#[derive(Clone, Debug, Default)]
+pub struct CancelToken {
+    cancelled: Arc<AtomicBool>,
+    deadline: Option<SteadyTime>,
+}
+
+impl CancelToken {
+    /// Creates a token which is only cancelled by calling `cancel()`.
+    pub fn new() -> Self {
+        CancelToken {
+            cancelled: Arc::new(AtomicBool::new(false)),
+            deadline: None,
+        }
+    }
+
+    /// Creates a token which is cancelled automatically once `timeout` has
+    /// passed (or earlier by calling `cancel()`).
+    pub fn with_timeout(timeout: Duration) -> Self {
+        CancelToken {
+            deadline: Some(SteadyTime::now() + timeout),
+            .. CancelToken::new()
+        }
+    }
+
+    /// Cancels the token and all its clones.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    /// Returns true if `cancel()` was called or the deadline has passed.
+    pub fn is_cancelled(&self) -> bool {
+        if self.cancelled.load(Ordering::SeqCst) {
+            return true;
+        }
+        match self.deadline {
+            Some(deadline) => SteadyTime::now() >= deadline,
+            None => false,
+        }
+    }
+}
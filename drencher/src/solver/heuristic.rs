@@ -1,35 +1,35 @@
 use board::Board;
-use std::collections::HashMap;
-use super::{Solution, Solver};
+use super::{CancelToken, FailureReason, Solution, Solver, SolveOutcome};
+use super::Progress;
 
 pub struct Heuristic;
 
 impl Solver for Heuristic {
 
-    fn solve(&self, mut b: Board) -> Result<Solution, Solution> {
+    fn solve_with_progress(
+        &self,
+        mut b: Board,
+        cancel: &CancelToken,
+        _: &mut dyn FnMut(&Progress),
+    ) -> SolveOutcome {
 
         let mut solution = Solution::new();
         while !b.is_drenched() {
-
-            // get border around "player"
-            let (_, mut border) = b.field_coords();
-            border.sort();
-            border.dedup();
-
-            // count color occurrence in border
-            let mut color_count = HashMap::new();
-            for pos in border {
-                *color_count.entry(b[pos]).or_insert(0) += 1;
+            if cancel.is_cancelled() {
+                return SolveOutcome::failed(solution, FailureReason::Aborted);
             }
 
-            // get most occurred color
-            let (color, _) = color_count.iter().max_by_key(|&(_, count)| count).unwrap();
+            // get most occurred color in the border around "player"
+            let color = b.adjacent_colors()
+                .into_iter()
+                .max_by_key(|&c| b.border_cells(c).len())
+                .unwrap();
 
             // drench with this color
-            b.drench(*color);
-            solution.push(*color);
+            b.drench(color);
+            solution.push(color);
         }
 
-        Ok(solution)
+        SolveOutcome::solved(solution)
     }
 }
@@ -1,15 +1,20 @@
-//! Interactive solver via Terminal.
+//! Interactive solver via an OpenGL window.
 //!
-//! This solver will interactively ask the user to choose a color
-//! and adds this color to the solution vector. The user is presented
-//! with the new board state.
+//! This solver opens a window showing the board and lets the user choose a
+//! color with the keys 1-9, 0 (for the tenth color) and A-F (for the colors
+//! eleven to sixteen). The chosen color is added to the solution vector. If
+//! no window can be created (e.g. on a headless server), the `Terminal`
+//! solver is used instead.
 
 use board::Board;
 use color::Color;
-use super::{Solver, Solution};
-// use std::io::{self, Write};
-use glium::{self, glutin, DisplayBuild, Surface};
-use glium::glutin::{ElementState, Event, VirtualKeyCode};
+use super::{CancelToken, FailureReason, Solver, Solution, SolveOutcome};
+use super::{Progress, Terminal};
+use glium::{self, glutin, Surface};
+use glium::glutin::{ElementState, Event, VirtualKeyCode, WindowEvent};
+use glium::vertex::{self, AttributeType, VertexFormat};
+use std::borrow::Cow;
+use std::env;
 
 // constants that modify the appearance
 const MARGIN: f32 = 0.1;
@@ -19,32 +24,63 @@ const CELL_DISTANCE: f32 = 0.0;
 /// Type definition for the solver.
 pub struct Human;
 
+/// A corner of the unit square, which is scaled and moved for each cell.
+#[derive(Copy, Clone)]
+struct Vertex {
+    #[allow(dead_code)] // only read by the vertex shader
+    position: [f32; 2],
+}
+
+// What `implement_vertex!` would generate, but without its uses of
+// uninitialized memory to find the offset of the only field.
+impl vertex::Vertex for Vertex {
+    fn build_bindings() -> VertexFormat {
+        Cow::Borrowed(&[
+            (Cow::Borrowed("position"), 0, AttributeType::F32F32, false),
+        ])
+    }
+}
+
 impl Solver for Human {
     // implement this to avoid printing all board states again
     fn prints_output(&self) -> bool { true }
 
-    fn solve(&self, mut board: Board) -> Result<Solution, Solution> {
-        let display = glutin::WindowBuilder::new()
-            .with_title("Drencher")
-            .with_vsync()
-            .with_srgb(Some(false))
-            .with_pixel_format(24, 8)
-            .build_glium()
-            .expect("creating OpenGL window failed!");
+    fn solve_with_progress(
+        &self,
+        mut board: Board,
+        cancel: &CancelToken,
+        _: &mut dyn FnMut(&Progress),
+    ) -> SolveOutcome {
+        if !display_available() {
+            println!("No display available, playing in the terminal instead!");
+            return Terminal.solve_until(board, cancel);
+        }
+
+        let mut events_loop = glutin::EventsLoop::new();
+        let window = glutin::WindowBuilder::new()
+            .with_title("Drencher");
+        let context = glutin::ContextBuilder::new()
+            .with_vsync(true)
+            .with_srgb(false)
+            .with_pixel_format(24, 8);
+        let display = match glium::Display::new(window, context, &events_loop)
+        {
+            Ok(display) => display,
+            Err(e) => {
+                // No display available -> fall back to the terminal player
+                println!(
+                    "Creating OpenGL window failed ({:?}), playing in the \
+                     terminal instead!",
+                    e,
+                );
+                return Terminal.solve_until(board, cancel);
+            }
+        };
 
         // calculate positions
         let (width, height) = display.get_framebuffer_dimensions();
         let (mut xmin, mut ymin, mut xmax, mut ymax) =
-            get_positions(width, height);
-        println!("{:?}", (xmin, xmin, xmax, ymax));
-
-
-        #[derive(Copy, Clone)]
-        struct Vertex {
-            position: [f32; 2],
-        }
-
-        implement_vertex!(Vertex, position);
+            get_positions(width, height, &board);
 
         let shape = vec![
             Vertex { position: [0.0, 0.0] },
@@ -96,14 +132,14 @@ impl Solver for Human {
             let mut target = display.draw();
             target.clear_color(0.02, 0.02, 0.05, 1.0);
 
-            let x_distance = (xmax - xmin) / (board.size() as f32);
-            let y_distance = (ymax - ymin) / (board.size() as f32);
-            for x in 0..board.size() {
-                for y in 0..board.size() {
+            let x_distance = (xmax - xmin) / (board.width() as f32);
+            let y_distance = (ymax - ymin) / (board.height() as f32);
+            for x in 0..board.width() {
+                for y in 0..board.height() {
 
                     target.draw(
                         &vertex_buffer,
-                        &indices,
+                        indices,
                         &program,
                         &uniform! {
                             scale: [
@@ -123,16 +159,31 @@ impl Solver for Human {
 
             target.finish().unwrap();
 
-            for ev in display.poll_events() {
-                match ev {
-                    Event::Closed |
-                    Event::KeyboardInput(ElementState::Pressed, _, Some(VirtualKeyCode::Escape))
-                        => break 'a,
-                    Event::KeyboardInput(ElementState::Pressed, _, Some(VirtualKeyCode::Return))
-                        if board.is_drenched() => return Ok(solution),
-                    Event::KeyboardInput(ElementState::Pressed, _, Some(vkc))
-                        if !board.is_drenched() =>
+            let mut events = Vec::new();
+            events_loop.poll_events(|ev| events.push(ev));
+            for ev in events {
+                let ev = match ev {
+                    Event::WindowEvent { event, .. } => event,
+                    _ => continue,
+                };
+                let key = match ev {
+                    WindowEvent::KeyboardInput { input, .. }
+                        if input.state == ElementState::Pressed =>
                     {
+                        input.virtual_keycode
+                    }
+                    _ => None,
+                };
+
+                match (ev, key) {
+                    (WindowEvent::CloseRequested, _) |
+                    (_, Some(VirtualKeyCode::Escape)) => break 'a,
+                    (_, Some(VirtualKeyCode::Return))
+                        if board.is_drenched() =>
+                    {
+                        return SolveOutcome::solved(solution);
+                    }
+                    (_, Some(vkc)) if !board.is_drenched() => {
                         let color = match vkc {
                             VirtualKeyCode::Key1 => Some(0),
                             VirtualKeyCode::Key2 => Some(1),
@@ -140,8 +191,25 @@ impl Solver for Human {
                             VirtualKeyCode::Key4 => Some(3),
                             VirtualKeyCode::Key5 => Some(4),
                             VirtualKeyCode::Key6 => Some(5),
+                            VirtualKeyCode::Key7 => Some(6),
+                            VirtualKeyCode::Key8 => Some(7),
+                            VirtualKeyCode::Key9 => Some(8),
+                            VirtualKeyCode::Key0 => Some(9),
+                            VirtualKeyCode::A => Some(10),
+                            VirtualKeyCode::B => Some(11),
+                            VirtualKeyCode::C => Some(12),
+                            VirtualKeyCode::D => Some(13),
+                            VirtualKeyCode::E => Some(14),
+                            VirtualKeyCode::F => Some(15),
                             _ => None,
-                        }.map(|n| Color::new(n));
+                        }.and_then(|n| {
+                            // ignore colors that don't exist on this board
+                            if n < board.colors() {
+                                Some(Color::new(n))
+                            } else {
+                                None
+                            }
+                        });
 
                         if let Some(color) = color {
                             if solution.last() != Some(&color) {
@@ -150,9 +218,11 @@ impl Solver for Human {
                             }
                         }
                     }
-                    Event::Resized(width, height) => {
+                    (WindowEvent::Resized(_), _) => {
+                        let (width, height) =
+                            display.get_framebuffer_dimensions();
                         let (nxmin, nymin, nxmax, nymax) =
-                            get_positions(width, height);
+                            get_positions(width, height, &board);
                         xmin = nxmin;
                         ymin = nymin;
                         xmax = nxmax;
@@ -164,38 +234,37 @@ impl Solver for Human {
             }
         }
 
+        SolveOutcome::failed(solution, FailureReason::Aborted)
+    }
+}
 
-        // // print initial board state
-        // println!("+++++ Initial board:");
-        // println!("{}", b);
-
-        // // while the user still inputs a color...
-        // while let Some(color) = prompt_color() {
-        //     println!("+++++ drenching {}", color);
-
-        //     out.push(color);
-        //     b.drench(color);
-        //     println!("{}", b);
-
-        //     if b.is_drenched() {
-        //         return Ok(out);
-        //     }
-        // }
-
-        // // apparently we didn't solve the board, but we don't have any more
-        // // inputs
-        Err(solution)
+/// Returns false if we know for sure that no window can be opened. On Linux
+/// and other unix systems, trying to open a window without X or Wayland
+/// panics deep within glutin, so we check the environment first.
+fn display_available() -> bool {
+    if cfg!(all(unix, not(target_os = "macos"))) {
+        env::var_os("DISPLAY").is_some()
+            || env::var_os("WAYLAND_DISPLAY").is_some()
+    } else {
+        true
     }
 }
 
-fn get_positions(width: u32, height: u32) -> (f32, f32, f32, f32) {
-    let xmargin = if width > height {
-        ((width - height) as f32) / (width as f32)
+/// Calculates the area (in OpenGL coordinates) in which the board is drawn.
+/// The area is centered and chosen such that the cells are square.
+fn get_positions(width: u32, height: u32, board: &Board)
+    -> (f32, f32, f32, f32)
+{
+    let window_ratio = (width as f32) / (height as f32);
+    let board_ratio = (board.width() as f32) / (board.height() as f32);
+
+    let xmargin = if window_ratio > board_ratio {
+        1.0 - board_ratio / window_ratio
     } else {
         0.0
     };
-    let ymargin = if height > width {
-        ((height - width) as f32) / (height as f32)
+    let ymargin = if board_ratio > window_ratio {
+        1.0 - window_ratio / board_ratio
     } else {
         0.0
     };
@@ -207,43 +276,3 @@ fn get_positions(width: u32, height: u32) -> (f32, f32, f32, f32) {
 
     (xmin, ymin, xmax, ymax)
 }
-
-// fn prompt_color() -> Option<Color> {
-//     // While the user gives us invalid input, we simply loop
-//     loop {
-//         // show all possible colors
-//         print!("Color to drench with next?");
-//         print!(" ({}->{}", 1, Color::new(0));
-//         for n in 1..6 {
-//             let c = Color::new(n);
-//             print!(", {}->{}", n + 1, c);
-//         }
-//         print!(")");
-
-//         // flush and return `None` if it wasn't sucessful (very unlikely)
-//         let res = io::stdout().flush();
-//         if res.is_err() {
-//             println!("Wasn't able to flush stdout!");
-//             return None;
-//         }
-
-//         // Read the users input and try to parse it as `u8`. Meanings of the
-//         // values of `maybe_num`:
-//         // - Err(true) -> an IO error occured
-//         // - Err(false) -> a parsing error occured
-//         // - Ok(_) -> the parsed `u8`
-//         let mut buffer = String::new();
-//         let maybe_num = io::stdin()
-//             .read_line(&mut buffer)
-//             .map_err(|_| true)
-//             .map(|_| buffer.trim())
-//             .and_then(|line| line.parse().map_err(|_| false));
-
-//         match maybe_num {
-//             Err(true) => return None,
-//             Err(false) => println!("Not a number!"),
-//             Ok(0) => println!("Colors are 1-indexed!"),
-//             Ok(n) => return Some(Color::new(n - 1)),
-//         }
-//     }
-// }
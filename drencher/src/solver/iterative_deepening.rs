@@ -0,0 +1,256 @@
+//! Iterative Deepening Solver
+//!
+//! Another optimal solver, made for machines with little memory. Instead of
+//! keeping whole levels of the game tree (like `Exact`) or all generated
+//! states (like `AStar`), it runs depth-first searches with an increasing
+//! limit on the number of moves. Branches whose number of moves plus lower
+//! bound (see `StateSpace::lower_bound`) exceed the limit are cut off. The
+//! first solution found is optimal, because all shorter ones were ruled out
+//! by the previous iterations.
+//!
+//! To avoid searching the same states over and over, states which didn't
+//! lead to a solution are stored in a transposition table of fixed size
+//! (`table_bytes`). If the table is full, old entries are overwritten. Apart
+//! from the table, memory is proportional to the search depth.
+
+use board::Board;
+use graph::RegionGraph;
+use super::{CancelToken, FailureReason, Solution, Solver, SolveOutcome};
+use super::Progress;
+use super::node_set::{NodeSet, MAX_NODES};
+use super::state_space::{Node, StateSpace, Table};
+use std::cmp;
+use time::SteadyTime;
+
+/// The iterative deepening solver. See module documentation for more
+/// information.
+pub struct IterativeDeepening {
+    table_bytes: usize,
+}
+
+/// Default memory used by the transposition table: 32 MiB.
+const DEFAULT_TABLE_BYTES: usize = 32 * 1024 * 1024;
+
+/// The cancel token is checked every time this many states were expanded.
+const CANCEL_CHECK_INTERVAL: u64 = 1024;
+
+/// Progress is reported every time this many states were expanded (and at
+/// the start of every iteration).
+const PROGRESS_INTERVAL: u64 = 1 << 16;
+
+impl IterativeDeepening {
+    /// Creates a solver with a transposition table of the default size.
+    pub fn new() -> Self {
+        IterativeDeepening::with_table_size(DEFAULT_TABLE_BYTES)
+    }
+
+    /// Creates a solver whose transposition table uses about `bytes` bytes
+    /// of memory. Smaller tables make the search slower, but never wrong.
+    pub fn with_table_size(bytes: usize) -> Self {
+        IterativeDeepening {
+            table_bytes: bytes,
+        }
+    }
+}
+
+impl Default for IterativeDeepening {
+    fn default() -> Self {
+        IterativeDeepening::new()
+    }
+}
+
+impl Solver for IterativeDeepening {
+    fn solve_with_progress(
+        &self,
+        b: Board,
+        cancel: &CancelToken,
+        progress: &mut dyn FnMut(&Progress),
+    ) -> SolveOutcome {
+        if b.is_drenched() {
+            return SolveOutcome {
+                optimal: true,
+                .. SolveOutcome::solved(vec![])
+            };
+        }
+
+        let g = RegionGraph::new(&b);
+        let table_bytes = self.table_bytes;
+        let outcome = with_node_set!(
+            g.len(),
+            search(&b, &g, table_bytes, cancel, progress)
+        );
+        outcome.unwrap_or_else(|| {
+            info!(
+                "the iterative deepening solver can't handle this board: it \
+                 has {} islands, but at most {} are supported",
+                g.len(),
+                MAX_NODES,
+            );
+            SolveOutcome::failed(vec![], FailureReason::Unsupported)
+        })
+    }
+}
+
+/// Runs the depth-first searches with increasing limits, working with sets
+/// of type `S`.
+fn search<S: NodeSet>(
+    b: &Board,
+    g: &RegionGraph,
+    table_bytes: usize,
+    cancel: &CancelToken,
+    progress: &mut dyn FnMut(&Progress),
+) -> SolveOutcome {
+    let space = StateSpace::<S>::new(b, g);
+    let root = space.root();
+    let bound = space.lower_bound(&root);
+
+    let mut dfs = Search {
+        space: space,
+        table: Table::new(table_bytes),
+        cancel: cancel,
+        progress: progress,
+        start: SteadyTime::now(),
+        path: Solution::new(),
+        best: Solution::new(),
+        best_owned: 0,
+        bound: bound,
+        next_bound: bound,
+        nodes_expanded: 0,
+        held: 0,
+        peak_frontier: 0,
+        pruned: 0,
+    };
+
+    loop {
+        debug!("searching with limit {}", dfs.bound);
+        dfs.report();
+
+        // The smallest limit at which a cut off branch would have been
+        // searched is the next limit.
+        dfs.next_bound = usize::max_value();
+        let step = dfs.visit(root);
+
+        let (moves, failure, optimal) = match step {
+            Step::Solved => (dfs.path, None, true),
+            Step::Aborted => (dfs.best, Some(FailureReason::Aborted), false),
+            Step::Failed => {
+                dfs.bound = dfs.next_bound;
+                continue;
+            }
+        };
+        return SolveOutcome {
+            moves: moves,
+            failure: failure,
+            optimal: optimal,
+            nodes_expanded: dfs.nodes_expanded,
+            peak_frontier: dfs.peak_frontier,
+            initial_bound: None,
+        };
+    }
+}
+
+/// Result of searching below one state.
+enum Step {
+    /// A solution within the limit was found, it's in `Search::path`.
+    Solved,
+    /// There is no solution within the limit.
+    Failed,
+    /// The search was cancelled.
+    Aborted,
+}
+
+/// State of one run of the solver.
+struct Search<'a, S> {
+    space: StateSpace<S>,
+    table: Table<S>,
+    cancel: &'a CancelToken,
+    progress: &'a mut dyn FnMut(&Progress),
+    start: SteadyTime,
+    /// Moves leading to the current state
+    path: Solution,
+    /// Moves leading to the expanded state owning the most regions, the best
+    /// partial solution
+    best: Solution,
+    best_owned: usize,
+    /// The current limit and the next one
+    bound: usize,
+    next_bound: usize,
+    // Statistics for the outcome and the progress reports
+    nodes_expanded: u64,
+    held: usize,
+    peak_frontier: usize,
+    pruned: u64,
+}
+
+impl<'a, S: NodeSet> Search<'a, S> {
+    /// Searches for a solution below `node` (reached with the moves in
+    /// `self.path`) within the current limit.
+    fn visit(&mut self, node: Node<S>) -> Step {
+        if node.is_goal() {
+            return Step::Solved;
+        }
+
+        // If this state was already searched with at least as many moves
+        // left without success, it will fail again.
+        let remaining = self.bound - self.path.len();
+        if self.table.get(&node.owned).is_some_and(|r| r >= remaining) {
+            self.pruned += 1;
+            return Step::Failed;
+        }
+
+        if self.nodes_expanded.is_multiple_of(CANCEL_CHECK_INTERVAL)
+            && self.cancel.is_cancelled()
+        {
+            return Step::Aborted;
+        }
+        self.nodes_expanded += 1;
+        if self.nodes_expanded.is_multiple_of(PROGRESS_INTERVAL) {
+            self.report();
+        }
+        if node.owned.len() > self.best_owned {
+            self.best_owned = node.owned.len();
+            self.best = self.path.clone();
+        }
+
+        // Cut off children which can't be solved within the limit and try
+        // the most promising ones first.
+        let depth = self.path.len() + 1;
+        let mut children = Vec::new();
+        for (color, child) in self.space.children(&node) {
+            let estimate = depth + self.space.lower_bound(&child);
+            if estimate > self.bound {
+                self.next_bound = cmp::min(self.next_bound, estimate);
+                self.pruned += 1;
+            } else {
+                children.push((estimate, color, child));
+            }
+        }
+        children.sort_by_key(|&(estimate, _, _)| estimate);
+
+        self.held += children.len();
+        self.peak_frontier = cmp::max(self.peak_frontier, self.held);
+
+        for &(_, color, child) in &children {
+            self.path.push(color);
+            match self.visit(child) {
+                Step::Failed => {
+                    self.path.pop();
+                }
+                step => return step,
+            }
+        }
+
+        self.held -= children.len();
+        self.table.insert(node.owned, remaining);
+        Step::Failed
+    }
+
+    fn report(&mut self) {
+        (self.progress)(&Progress {
+            depth: self.bound,
+            frontier: self.held,
+            pruned: self.pruned,
+            elapsed: SteadyTime::now() - self.start,
+        });
+    }
+}
@@ -1,29 +1,154 @@
 use color::Color;
 use board::Board;
+use std::fmt;
+use time::Duration;
 
 // define solver-implementations, each in it's own module
+#[macro_use]
+mod node_set;
+mod state_space;
+mod cancel;
 mod random;
 mod exact;
+mod astar;
+mod iterative_deepening;
+mod branch_and_bound;
+#[cfg(feature = "gui")]
 mod human;
+mod terminal;
 mod heuristic;
 mod mod_count;
 
+pub use self::cancel::CancelToken;
 pub use self::random::Random;
 pub use self::exact::Exact;
+pub use self::astar::AStar;
+pub use self::iterative_deepening::IterativeDeepening;
+pub use self::branch_and_bound::BranchAndBound;
+#[cfg(feature = "gui")]
 pub use self::human::Human;
+pub use self::terminal::Terminal;
 pub use self::heuristic::Heuristic;
 pub use self::mod_count::ModCount;
 
 // typedef, thanks to Julian
 pub type Solution = Vec<Color>;
 
+/// Why a solver didn't solve a board.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FailureReason {
+    /// The solver gave up after the maximum number of moves it tries.
+    MoveLimit,
+    /// The solve was stopped before it finished (e.g. the player quit).
+    Aborted,
+    /// The solver can't handle this board (e.g. it's too big).
+    Unsupported,
+}
+
+impl fmt::Display for FailureReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            FailureReason::MoveLimit => "move limit reached",
+            FailureReason::Aborted => "aborted",
+            FailureReason::Unsupported => "board not supported",
+        }.fmt(f)
+    }
+}
+
+/// Everything a solver reports about one solve: the moves, whether they win
+/// the game and a few statistics. Counters that don't apply to a solver stay
+/// zero.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SolveOutcome {
+    /// The moves found by the solver. Even unsuccessful solvers can return
+    /// some moves.
+    pub moves: Solution,
+    /// `None` if the moves win the game, otherwise the reason why they don't.
+    pub failure: Option<FailureReason>,
+    /// True if the solver proved that there is no shorter solution.
+    pub optimal: bool,
+    /// Number of states of the game tree the solver expanded.
+    pub nodes_expanded: u64,
+    /// Maximum number of states the solver kept at once.
+    pub peak_frontier: usize,
+    /// Length of the solution the solver started from (e.g. one found by a
+    /// heuristic), if any. Comparing it with the number of moves shows how
+    /// much the solver improved it.
+    pub initial_bound: Option<usize>,
+}
+
+impl SolveOutcome {
+    /// Creates the outcome of a successful solve.
+    pub fn solved(moves: Solution) -> Self {
+        SolveOutcome {
+            moves: moves,
+            failure: None,
+            optimal: false,
+            nodes_expanded: 0,
+            peak_frontier: 0,
+            initial_bound: None,
+        }
+    }
+
+    /// Creates the outcome of an unsuccessful solve.
+    pub fn failed(moves: Solution, reason: FailureReason) -> Self {
+        SolveOutcome {
+            failure: Some(reason),
+            .. SolveOutcome::solved(moves)
+        }
+    }
+
+    /// Returns true if the moves win the game.
+    pub fn is_solved(&self) -> bool {
+        self.failure.is_none()
+    }
+}
+
+/// A snapshot of a running search, passed to the progress callback of
+/// `Solver::solve_with_progress`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Progress {
+    /// Depth of the game tree the solver is working on (number of moves).
+    pub depth: usize,
+    /// Number of states in the current level of the game tree.
+    pub frontier: usize,
+    /// Number of states removed so far because they can't lead to a better
+    /// solution.
+    pub pruned: u64,
+    /// Time since the solve started.
+    pub elapsed: Duration,
+}
+
 /// Something that can solve our game from an initial board.
 pub trait Solver: Sync {
     /// Given a board, the solver has to return a list of moves. Those moves
-    /// either win the game (`Ok(..)`) or don't (`Err(..)`), in which case the
-    /// solver wasn't able to find a winning solution. The `Err` value can
-    /// still contain a solution vector.
-    fn solve(&self, b: Board) -> Result<Solution, Solution>;
+    /// either win the game or don't, in which case the outcome says why the
+    /// solver wasn't able to find a winning solution. The moves of a failed
+    /// solve can still be non-empty.
+    ///
+    /// Solvers which might run for a long time check `cancel` regularly.
+    /// Once it's cancelled, they stop and return the best partial solution
+    /// found so far with the failure reason `Aborted`.
+    ///
+    /// Searching solvers call `progress` every now and then (e.g. once per
+    /// level of the game tree) to report how far they got. Solvers which
+    /// don't search never call it.
+    fn solve_with_progress(
+        &self,
+        b: Board,
+        cancel: &CancelToken,
+        progress: &mut dyn FnMut(&Progress),
+    ) -> SolveOutcome;
+
+    /// Like `solve_with_progress`, but without progress reports.
+    fn solve_until(&self, b: Board, cancel: &CancelToken) -> SolveOutcome {
+        self.solve_with_progress(b, cancel, &mut |_| {})
+    }
+
+    /// Solves the board without a time limit. See `solve_with_progress`.
+    fn solve(&self, b: Board) -> SolveOutcome {
+        self.solve_until(b, &CancelToken::new())
+    }
 
     /// Returns true if the solver already outputs every step of the game. This
     /// is probably only the case for the 'human' solver/player.
@@ -1,25 +1,29 @@
-use super::{Solver, Solution};
+use super::{CancelToken, FailureReason, Solver, Solution, SolveOutcome};
+use super::Progress;
 use board::Board;
 use color::Color;
 
 pub struct ModCount;
 
 impl Solver for ModCount {
-    fn solve(&self, b: Board) -> Result<Solution, Solution> {
+    fn solve_with_progress(
+        &self,
+        mut b: Board,
+        cancel: &CancelToken,
+        _: &mut dyn FnMut(&Progress),
+    ) -> SolveOutcome {
+        let colors = b.colors() as usize;
+        let mut solution = Solution::new();
 
-        Ok(
-            (0..)
-            .map(|i| Color::new(i % 6))
-            .scan(b, |b, color| {
-                let drenched = b.is_drenched();
-                if !drenched {
-                    b.drench(color);
-                }
-                Some((drenched, color))
-            })
-            .take_while(|&(is_drenched, _)| !is_drenched)
-            .map(|(_, color)| color)
-            .collect()
-        )
+        // cycle through all colors until the board is drenched
+        while !b.is_drenched() {
+            if cancel.is_cancelled() {
+                return SolveOutcome::failed(solution, FailureReason::Aborted);
+            }
+            let color = Color::new((solution.len() % colors) as u8);
+            b.drench(color);
+            solution.push(color);
+        }
+        SolveOutcome::solved(solution)
     }
 }
@@ -1,8 +1,9 @@
 //! Random solver.
 //!
 //! A solver that just outputs a random valid move. If the solution wasn't
-//! found after `MAX_MOVES` moves, an error is returned.
-use super::{Solver, Solution};
+//! found after `MAX_MOVES` moves, the solver gives up.
+use super::{CancelToken, FailureReason, Solver, Solution, SolveOutcome};
+use super::Progress;
 use board::Board;
 use color::Color;
 use rand;
@@ -16,23 +17,32 @@ pub struct Random;
 const MAX_MOVES: usize = 400;
 
 impl Solver for Random {
-    fn solve(&self, mut b: Board) -> Result<Solution, Solution> {
+    fn solve_with_progress(
+        &self,
+        mut b: Board,
+        cancel: &CancelToken,
+        _: &mut dyn FnMut(&Progress),
+    ) -> SolveOutcome {
         // Initialize RNG, range and solution vector
         let mut rng = rand::thread_rng();
-        let range = Range::new(0, 6);
+        let range = Range::new(0, b.colors());
         let mut solution = Solution::new();
 
         // just add more random moves until we actually solved game
-        while !b.is_drenched() && solution.len() < MAX_MOVES {
+        while !b.is_drenched() && solution.len() < MAX_MOVES
+            && !cancel.is_cancelled()
+        {
             let color = Color::new(range.ind_sample(&mut rng));
             solution.push(color);
             b.drench(color);
         }
 
         if b.is_drenched() {
-            Ok(solution)
+            SolveOutcome::solved(solution)
+        } else if cancel.is_cancelled() {
+            SolveOutcome::failed(solution, FailureReason::Aborted)
         } else {
-            Err(solution)
+            SolveOutcome::failed(solution, FailureReason::MoveLimit)
         }
     }
 }
@@ -0,0 +1,200 @@
+//! The game tree over the region graph, shared by the informed exact solvers.
+//!
+//! A state of the game is fully described by the set of regions the player
+//! owns. Together with the set of regions adjacent to the owned ones, this is
+//! a `Node`. The `StateSpace` knows the graph (as `NodeSet`s) and creates
+//! the children of nodes and admissible lower bounds for the number of moves
+//! still needed. `Table` is a transposition table of fixed size.
+
+use board::Board;
+use color::Color;
+use graph::{RegionGraph, ROOT};
+use super::node_set::NodeSet;
+use std::cmp;
+use std::mem;
+use util::ColorSet;
+
+
+/// One state of the game: the owned regions and the regions adjacent to
+/// them. The game is won if nothing is adjacent anymore.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Node<S> {
+    pub owned: S,
+    pub adjacent: S,
+}
+
+impl<S: NodeSet> Node<S> {
+    /// Returns true if all regions are owned.
+    pub fn is_goal(&self) -> bool {
+        self.adjacent.is_empty()
+    }
+}
+
+/// The region graph of a board, prepared for fast searches with node sets of
+/// type `S`. All regions of the graph have to fit into `S`.
+pub struct StateSpace<S> {
+    /// For each region the set of its neighbors.
+    adjacent: Vec<S>,
+    /// For each color the set of regions with that color.
+    colored: Vec<S>,
+    /// For each region its color.
+    color_of: Vec<Color>,
+}
+
+impl<S: NodeSet> StateSpace<S> {
+    /// Prepares the region graph of a freshly built graph of `b`.
+    pub fn new(b: &Board, g: &RegionGraph) -> Self {
+        // As the graph is freshly built, the node ids are exactly
+        // `0..g.len()`.
+        let adjacent = g.regions().map(|id| {
+            let mut set = S::empty();
+            for &neighbor in g.neighbors(id) {
+                set.insert(neighbor);
+            }
+            set
+        }).collect();
+
+        let mut colored = vec![S::empty(); b.colors() as usize];
+        for id in g.regions() {
+            colored[g.color(id).tag as usize].insert(id);
+        }
+
+        StateSpace {
+            adjacent: adjacent,
+            colored: colored,
+            color_of: g.regions().map(|id| g.color(id)).collect(),
+        }
+    }
+
+    /// Returns the initial state: only the root is owned.
+    pub fn root(&self) -> Node<S> {
+        Node {
+            owned: S::with_only_first(),
+            adjacent: self.adjacent[ROOT as usize],
+        }
+    }
+
+    /// Returns all children of the given node together with the move
+    /// leading to them. Moves which don't capture anything are left out. If
+    /// one move captures all remaining regions of its color, it's the only
+    /// child: no other move can be better than that.
+    pub fn children(&self, node: &Node<S>) -> Vec<(Color, Node<S>)> {
+        let mut colors = Vec::new();
+        for (tag, colored) in self.colored.iter().enumerate() {
+            let num_adj = S::count_common_elements(&node.adjacent, colored);
+            let num_remaining =
+                S::count_elements_only_in_first(colored, &node.owned);
+
+            if num_adj > 0 && num_adj == num_remaining {
+                colors.clear();
+                colors.push(tag);
+                break;
+            } else if num_adj > 0 {
+                colors.push(tag);
+            }
+        }
+
+        colors.into_iter().map(|tag| {
+            let captured = S::intersection(&node.adjacent, &self.colored[tag]);
+            let owned = S::union(&node.owned, &captured);
+
+            let mut adjacent = node.adjacent;
+            for id in captured.iter() {
+                adjacent.union_with(&self.adjacent[id as usize]);
+            }
+            adjacent.without(&owned);
+
+            let child = Node {
+                owned: owned,
+                adjacent: adjacent,
+            };
+            (Color::new(tag as u8), child)
+        }).collect()
+    }
+
+    /// Returns an admissible lower bound for the number of moves needed to
+    /// win the game from the given node: it never overestimates.
+    ///
+    /// Every move captures adjacent regions only, so regions at distance `k`
+    /// from the owned ones can't be captured before the `k`-th move. And
+    /// every color still on the board needs a move of its own. So if regions
+    /// of `n` different colors are further away than `j`, at least `j + n`
+    /// moves are needed. The bound is the maximum over all `j`, which is at
+    /// least the number of missing colors (`j = 0`) and the maximum distance
+    /// of any region. It's also consistent: as every move decreases all
+    /// distances by at most one, it decreases the bound by at most one.
+    pub fn lower_bound(&self, node: &Node<S>) -> usize {
+        // The colors of the layers of regions with the same distance to the
+        // owned ones, starting with the adjacent regions (distance 1).
+        let mut layer_colors = Vec::new();
+        let mut reached = node.owned;
+        let mut layer = node.adjacent;
+        while !layer.is_empty() {
+            reached.union_with(&layer);
+
+            let mut colors = ColorSet::new();
+            let mut next = S::empty();
+            for id in layer.iter() {
+                colors.set(self.color_of[id as usize]);
+                next.union_with(&self.adjacent[id as usize]);
+            }
+            next.without(&reached);
+
+            layer_colors.push(colors);
+            layer = next;
+        }
+
+        // Going from the outermost layer inwards, `beyond` contains the
+        // colors of all regions further away than `j`.
+        let mut beyond = ColorSet::new();
+        let mut bound = 0;
+        for (j, colors) in layer_colors.iter().enumerate().rev() {
+            for color in colors {
+                beyond.set(color);
+            }
+            bound = cmp::max(bound, j + beyond.len());
+        }
+        bound
+    }
+}
+
+/// Transposition table: stores one number per state, like the number of
+/// moves a state was reached with. It has a fixed number of slots; every
+/// state can only be stored in the slot given by its hash and replaces the
+/// state stored there before. So the table never grows, but it might forget
+/// states.
+pub struct Table<S> {
+    slots: Vec<Option<(S, usize)>>,
+}
+
+impl<S: NodeSet> Table<S> {
+    /// Creates a table using about `bytes` bytes of memory.
+    pub fn new(bytes: usize) -> Self {
+        let len = cmp::max(1, bytes / mem::size_of::<Option<(S, usize)>>());
+        Table {
+            slots: vec![None; len],
+        }
+    }
+
+    /// Returns the number stored for the state owning the given regions.
+    pub fn get(&self, owned: &S) -> Option<usize> {
+        match self.slots[self.slot(owned)] {
+            Some((ref key, value)) if key == owned => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Stores a number for the state owning the given regions.
+    pub fn insert(&mut self, owned: S, value: usize) {
+        let slot = self.slot(&owned);
+        self.slots[slot] = Some((owned, value));
+    }
+
+    fn slot(&self, owned: &S) -> usize {
+        // FNV-1a over the blocks of the set
+        let hash = owned.blocks().iter().fold(0xcbf2_9ce4_8422_2325, |h, &b| {
+            (h ^ b).wrapping_mul(0x100_0000_01b3)
+        });
+        (hash % self.slots.len() as u64) as usize
+    }
+}
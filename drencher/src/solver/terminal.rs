@@ -0,0 +1,118 @@
+//! Interactive solver via Terminal.
+//!
+//! This solver will interactively ask the user to choose a color and adds
+//! this color to the solution vector. The user is presented with the new
+//! board state after every move. In contrast to the `Human` solver, it
+//! doesn't need a display or OpenGL and thus works over SSH, too.
+
+use board::Board;
+use color::Color;
+use super::{CancelToken, FailureReason, Solver, Solution, SolveOutcome};
+use super::Progress;
+use std::io::{self, Write};
+
+/// Type definition for the solver.
+pub struct Terminal;
+
+/// One command the user can enter at the prompt.
+enum Command {
+    Drench(Color),
+    Undo,
+    Quit,
+}
+
+impl Solver for Terminal {
+    // implement this to avoid printing all board states again
+    fn prints_output(&self) -> bool { true }
+
+    fn solve_with_progress(
+        &self,
+        mut board: Board,
+        _: &CancelToken,
+        _: &mut dyn FnMut(&Progress),
+    ) -> SolveOutcome {
+        let mut solution = Solution::new();
+
+        // All previous board states, used to undo moves
+        let mut history = Vec::new();
+
+        while !board.is_drenched() {
+            println!("+++++ Move {}:", solution.len() + 1);
+            println!("{}", board);
+
+            match prompt_command(board.colors()) {
+                Some(Command::Drench(color)) => {
+                    // Drenching with the color of the field doesn't change
+                    // anything, so we don't count it as a move.
+                    if color == board[(0, 0)] {
+                        println!("The field already has this color!");
+                        continue;
+                    }
+
+                    history.push(board.clone());
+                    board.drench(color);
+                    solution.push(color);
+                }
+                Some(Command::Undo) => {
+                    match history.pop() {
+                        Some(old) => {
+                            board = old;
+                            solution.pop();
+                        }
+                        None => println!("Nothing to undo!"),
+                    }
+                }
+                // The user quit or stdin was closed
+                Some(Command::Quit) | None => {
+                    return SolveOutcome::failed(
+                        solution,
+                        FailureReason::Aborted,
+                    );
+                }
+            }
+        }
+
+        println!("+++++ Final board ({} moves):", solution.len());
+        println!("{}", board);
+        SolveOutcome::solved(solution)
+    }
+}
+
+/// Asks the user for the next command until a valid one is entered. Returns
+/// `None` if stdin was closed or an IO error occured.
+fn prompt_command(colors: u8) -> Option<Command> {
+    // While the user gives us invalid input, we simply loop
+    loop {
+        // show all possible colors
+        print!("Color to drench with next? ({}->{}", 1, Color::new(0));
+        for n in 1..colors {
+            print!(", {}->{}", n + 1, Color::new(n));
+        }
+        print!(", u->undo, q->quit) ");
+
+        // flush and return `None` if it wasn't sucessful (very unlikely)
+        if io::stdout().flush().is_err() {
+            println!("Wasn't able to flush stdout!");
+            return None;
+        }
+
+        let mut buffer = String::new();
+        match io::stdin().read_line(&mut buffer) {
+            // EOF: nothing more to read
+            Ok(0) | Err(_) => return None,
+            Ok(_) => {}
+        }
+
+        match buffer.trim() {
+            "u" | "undo" => return Some(Command::Undo),
+            "q" | "quit" => return Some(Command::Quit),
+            other => match other.parse::<u8>() {
+                Ok(n) if n >= 1 && n <= colors => {
+                    return Some(Command::Drench(Color::new(n - 1)));
+                }
+                Ok(_) => println!("Colors are numbered from 1 to {}!", colors),
+                Err(_) => println!("Not a valid command!"),
+            },
+        }
+    }
+}
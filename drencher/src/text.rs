@@ -0,0 +1,190 @@
+//! Plain-text board format.
+//!
+//! Boards are written as one row per line with one character per cell. The
+//! character is the color tag as (hexadecimal) digit, so `0`-`9` for the
+//! first ten colors and `a`-`f` (or `A`-`F`) for the remaining ones. An
+//! example:
+//!
+//! ```text
+//! # A 4x3 board
+//! colors: 6
+//! 0123
+//! 4501
+//! 2345
+//! ```
+//!
+//! Empty lines and lines starting with `#` are ignored. The optional
+//! `colors:` line has to come before the first row and states the number of
+//! colors of the board. Without it, the board has the standard six colors
+//! (or more, if a higher color tag is used).
+
+use board::Board;
+use color::{Color, DEFAULT_COLORS, MAX_COLORS};
+use std::cmp;
+use std::error::Error;
+use std::fmt;
+use std::io::{self, Write};
+use std::str::FromStr;
+
+
+/// Error returned when parsing a board in the text format fails. Lines and
+/// columns are 1-based.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseBoardError {
+    pub line: usize,
+    pub column: usize,
+    pub msg: String,
+}
+
+impl fmt::Display for ParseBoardError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}, column {}: {}", self.line, self.column, self.msg)
+    }
+}
+
+impl Error for ParseBoardError {
+    fn description(&self) -> &str {
+        &self.msg
+    }
+}
+
+impl FromStr for Board {
+    type Err = ParseBoardError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        macro_rules! bail {
+            ($line:expr, $col:expr, $($arg:tt)*) => {
+                return Err(ParseBoardError {
+                    line: $line,
+                    column: $col,
+                    msg: format!($($arg)*),
+                })
+            }
+        }
+
+        let mut colors = None;
+        let mut width = None;
+        let mut height = 0;
+        let mut max_tag = 0;
+        let mut cells = Vec::new();
+        let mut last_line = 0;
+
+        for (i, line) in s.lines().enumerate() {
+            let line_nr = i + 1;
+            last_line = line_nr;
+            let trimmed = line.trim();
+
+            // Skip comments and empty lines
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+
+            // The optional color header
+            if let Some(value) = trimmed.strip_prefix("colors:") {
+                let column = line.find("colors:").unwrap() + 1;
+                if width.is_some() {
+                    bail!(
+                        line_nr,
+                        column,
+                        "'colors:' has to come before the first row"
+                    );
+                }
+                if colors.is_some() {
+                    bail!(line_nr, column, "duplicate 'colors:' line");
+                }
+
+                let value = value.trim();
+                match value.parse::<u8>() {
+                    Ok(n) if (1..=MAX_COLORS).contains(&n) => colors = Some(n),
+                    _ => bail!(
+                        line_nr,
+                        column + "colors:".len(),
+                        "invalid number of colors '{}' (has to be between \
+                         1 and {})",
+                        value,
+                        MAX_COLORS,
+                    ),
+                }
+                continue;
+            }
+
+            // A row of cells
+            let indent = line.len() - line.trim_start().len();
+            let mut row_len = 0;
+            for (j, c) in trimmed.chars().enumerate() {
+                let tag = match c.to_digit(16) {
+                    Some(tag) => tag as u8,
+                    None => bail!(
+                        line_nr,
+                        indent + j + 1,
+                        "invalid cell '{}' (expected a digit or a letter a-f)",
+                        c,
+                    ),
+                };
+                if let Some(colors) = colors {
+                    if tag >= colors {
+                        bail!(
+                            line_nr,
+                            indent + j + 1,
+                            "color {} doesn't exist on a board with {} colors",
+                            c,
+                            colors,
+                        );
+                    }
+                }
+
+                max_tag = cmp::max(max_tag, tag);
+                cells.push(Color::new(tag));
+                row_len += 1;
+            }
+
+            match width {
+                None => width = Some(row_len),
+                Some(w) if w != row_len => bail!(
+                    line_nr,
+                    indent + cmp::min(w, row_len) + 1,
+                    "row has {} cells, but the first row has {}",
+                    row_len,
+                    w,
+                ),
+                _ => {}
+            }
+            height += 1;
+        }
+
+        let width = match width {
+            Some(w) => w,
+            None => {
+                bail!(last_line + 1, 1, "the board doesn't contain any cells")
+            }
+        };
+        let max = ::std::u16::MAX as usize;
+        if width > max || height > max {
+            bail!(last_line, 1, "the board is too big ({}x{})", width, height);
+        }
+
+        let colors = colors.unwrap_or(cmp::max(DEFAULT_COLORS, max_tag + 1));
+        Ok(Board::from_cells(width as u16, height as u16, colors, cells))
+    }
+}
+
+impl Board {
+    /// Writes the board in the text format (see module documentation).
+    pub fn write_text<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        writeln!(w, "colors: {}", self.colors())?;
+        for y in 0..self.height() {
+            for x in 0..self.width() {
+                write!(w, "{:x}", self[(x, y)].tag)?;
+            }
+            writeln!(w)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the board in the text format (see module documentation).
+    pub fn to_text(&self) -> String {
+        let mut out = Vec::new();
+        self.write_text(&mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+}
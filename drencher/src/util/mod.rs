@@ -1,9 +1,11 @@
-use color::Color;
+use color::{Color, MAX_COLORS};
+use board::Pos;
 use std::iter::repeat;
 use std::ops;
 
+#[derive(Default)]
 pub struct ColorSet {
-    data: u8,
+    data: u16,
 }
 
 impl ColorSet {
@@ -24,6 +26,14 @@ impl ColorSet {
     pub fn clear(&mut self) {
         self.data = 0;
     }
+
+    pub fn len(&self) -> usize {
+        self.data.count_ones() as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data == 0
+    }
 }
 
 impl<'a> IntoIterator for &'a ColorSet {
@@ -45,10 +55,10 @@ pub struct ColorSetIter<'a> {
 impl<'a> Iterator for ColorSetIter<'a> {
     type Item = Color;
     fn next(&mut self) -> Option<Self::Item> {
-        while self.pos < 6 && !self.set.is_set(Color::new(self.pos)) {
+        while self.pos < MAX_COLORS && !self.set.is_set(Color::new(self.pos)) {
             self.pos += 1;
         }
-        if self.pos < 6 {
+        if self.pos < MAX_COLORS {
             self.pos += 1;
             Some(Color::new(self.pos - 1))
         } else {
@@ -58,65 +68,69 @@ impl<'a> Iterator for ColorSetIter<'a> {
 }
 
 
+#[derive(Clone)]
 pub struct CellMap<T> {
-    size: u8,
+    width: u16,
+    height: u16,
     cells: Vec<T>,
 }
 
 #[allow(dead_code)]
 impl<T> CellMap<T> {
-    pub fn new(size: u8, obj: T) -> Self
+    pub fn new(width: u16, height: u16, obj: T) -> Self
         where T: Clone
     {
         CellMap {
-            size: size,
-            cells: repeat(obj).take((size as usize).pow(2)).collect(),
+            width: width,
+            height: height,
+            cells: vec![obj; width as usize * height as usize],
         }
     }
 
-    pub fn default(size: u8) -> Self
+    pub fn default(width: u16, height: u16) -> Self
         where T: Default
     {
         let cells = repeat(())
             .map(|_| T::default())
-            .take((size as usize).pow(2))
+            .take(width as usize * height as usize)
             .collect();
         CellMap {
-            size: size,
+            width: width,
+            height: height,
             cells: cells,
         }
     }
 }
 
 
-impl<T> ops::Index<(u8, u8)> for CellMap<T> {
+impl<T> ops::Index<Pos> for CellMap<T> {
     type Output = T;
-    fn index(&self, (x, y): (u8, u8)) -> &Self::Output {
-        if x > self.size || y > self.size {
+    fn index(&self, (x, y): Pos) -> &Self::Output {
+        if x >= self.width || y >= self.height {
             panic!(
-                "x ({}) or y ({}) greater than size ({})",
-                x, y, self.size
+                "x ({}) or y ({}) out of bounds (width {}, height {})",
+                x, y, self.width, self.height
             );
         }
 
         &self.cells[
-            (y as usize) * (self.size as usize)
+            (y as usize) * (self.width as usize)
                 + (x as usize)
         ]
     }
 }
 
-impl<T> ops::IndexMut<(u8, u8)> for CellMap<T> {
-    fn index_mut(&mut self, (x, y): (u8, u8)) -> &mut Self::Output {
-        if x > self.size || y > self.size {
+impl<T> ops::IndexMut<Pos> for CellMap<T> {
+    fn index_mut(&mut self, (x, y): Pos) -> &mut Self::Output {
+        if x >= self.width || y >= self.height {
             panic!(
-                "x ({}) or y ({}) greater than size ({})",
-                x, y, self.size
+                "x ({}) or y ({}) out of bounds (width {}, height {})",
+                x, y, self.width, self.height
             );
         }
 
         &mut self.cells[
-            (y as usize) * (self.size as usize)
+            (y as usize) * (self.width as usize)
                 + (x as usize)
         ]
     }
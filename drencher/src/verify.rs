@@ -0,0 +1,97 @@
+//! Checking solutions.
+//!
+//! `verify` replays a sequence of moves on a board and reports for every move
+//! whether it was legal, whether it was redundant and how many cells it
+//! captured. This works for solutions of any origin, not only for the ones
+//! of the solvers in this crate.
+
+use board::Board;
+use color::Color;
+
+
+/// What happened during one move.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MoveReport {
+    pub color: Color,
+    /// False if the color doesn't exist on the board. Illegal moves are
+    /// skipped.
+    pub legal: bool,
+    /// True if the move had no effect: either the field already had that
+    /// color or no cell was captured.
+    pub redundant: bool,
+    /// Number of cells captured by the move.
+    pub captured: usize,
+    /// Number of cells owned by the player after the move.
+    pub owned: usize,
+}
+
+/// Result of replaying a solution on a board.
+#[derive(Clone, Debug)]
+pub struct Verification {
+    /// One report per move, in order.
+    pub moves: Vec<MoveReport>,
+    /// True if the board is drenched after the last move.
+    pub drenched: bool,
+    /// Number of moves after which the board was drenched for the first
+    /// time, if it was drenched at all.
+    pub drenched_after: Option<usize>,
+}
+
+impl Verification {
+    /// Returns true if all moves are legal and the board ends drenched.
+    pub fn is_valid(&self) -> bool {
+        self.drenched && self.illegal_count() == 0
+    }
+
+    /// Returns the number of illegal moves.
+    pub fn illegal_count(&self) -> usize {
+        self.moves.iter().filter(|m| !m.legal).count()
+    }
+
+    /// Returns the number of legal but redundant moves.
+    pub fn redundant_count(&self) -> usize {
+        self.moves.iter().filter(|m| m.legal && m.redundant).count()
+    }
+}
+
+/// Replays the moves on (a copy of) the board and reports what happened.
+pub fn verify(b: &Board, moves: &[Color]) -> Verification {
+    let mut board = b.clone();
+    let mut reports = Vec::with_capacity(moves.len());
+    let mut drenched_after = if board.is_drenched() { Some(0) } else { None };
+
+    for (i, &color) in moves.iter().enumerate() {
+        let before = board.field_size();
+        let report = if color.tag < board.colors() {
+            let same_color = board[(0, 0)] == color;
+            board.drench(color);
+            let captured = board.field_size() - before;
+            MoveReport {
+                color: color,
+                legal: true,
+                redundant: same_color || captured == 0,
+                captured: captured,
+                owned: board.field_size(),
+            }
+        } else {
+            MoveReport {
+                color: color,
+                legal: false,
+                redundant: true,
+                captured: 0,
+                owned: before,
+            }
+        };
+        reports.push(report);
+
+        if drenched_after.is_none() && board.is_drenched() {
+            drenched_after = Some(i + 1);
+        }
+    }
+
+    Verification {
+        moves: reports,
+        drenched: board.is_drenched(),
+        drenched_after: drenched_after,
+    }
+}
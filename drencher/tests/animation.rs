@@ -0,0 +1,130 @@
+extern crate drencher;
+
+use drencher::{Board, Color, Error};
+use drencher::animation::{self, AnimationOptions};
+use drencher::render::{self, RenderOptions};
+
+
+fn moves() -> Vec<Color> {
+    vec![Color::new(2), Color::new(0), Color::new(1), Color::new(2)]
+}
+
+fn options() -> AnimationOptions {
+    AnimationOptions {
+        render: RenderOptions {
+            cell_size: 8,
+            .. RenderOptions::default()
+        },
+        delay: 250,
+        caption: true,
+    }
+}
+
+/// Returns the types of all chunks of a PNG file.
+fn chunks(png: &[u8]) -> Vec<String> {
+    assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+    let mut out = Vec::new();
+    let mut pos = 8;
+    while pos < png.len() {
+        let len = (png[pos] as usize) << 24 | (png[pos + 1] as usize) << 16
+            | (png[pos + 2] as usize) << 8 | png[pos + 3] as usize;
+        out.push(String::from_utf8(png[pos + 4..pos + 8].to_vec()).unwrap());
+        pos += 12 + len;
+    }
+    assert_eq!(pos, png.len());
+    out
+}
+
+#[test]
+fn frames() {
+    let board = Board::deterministic_random(6, 6, 3, 1);
+    let frames = animation::replay_frames(&board, &moves(), &options())
+        .unwrap();
+    assert_eq!(frames.len(), 5);
+
+    // The caption needs some space below the board
+    let plain = render::render_board(&board, &options().render).unwrap();
+    for frame in &frames {
+        assert_eq!(frame.width(), plain.width());
+        assert!(frame.height() > plain.height());
+    }
+
+    let no_caption = AnimationOptions {
+        caption: false,
+        .. options()
+    };
+    let frames = animation::replay_frames(&board, &[], &no_caption).unwrap();
+    assert_eq!(frames.len(), 1);
+    assert_eq!(frames[0].height(), plain.height());
+}
+
+#[test]
+fn apng() {
+    let board = Board::deterministic_random(5, 4, 3, 2);
+    let mut out = Vec::new();
+    animation::write_apng(&mut out, &board, &moves(), &options()).unwrap();
+
+    let chunks = chunks(&out);
+    assert_eq!(chunks[0], "IHDR");
+    assert_eq!(chunks[1], "acTL");
+    assert_eq!(chunks.last().unwrap(), "IEND");
+    assert_eq!(chunks.iter().filter(|c| *c == "fcTL").count(), 5);
+    assert_eq!(chunks.iter().filter(|c| *c == "IDAT").count(), 1);
+    assert_eq!(chunks.iter().filter(|c| *c == "fdAT").count(), 4);
+}
+
+#[test]
+fn gif() {
+    let board = Board::deterministic_random(5, 4, 3, 2);
+    let mut out = Vec::new();
+    animation::write_gif(&mut out, &board, &moves(), &options()).unwrap();
+    assert_eq!(&out[..6], b"GIF89a");
+    assert_eq!(*out.last().unwrap(), 0x3b);
+}
+
+#[test]
+fn unknown_extension() {
+    let board = Board::uniform(2, 2, 6);
+    let res = animation::save_replay("replay.mp4", &board, &[], &options());
+    assert!(res.is_err());
+}
+
+#[test]
+fn too_big() {
+    let board = Board::uniform(2, 2, 6);
+    let moves = vec![Color::new(0); animation::MAX_FRAMES];
+    let limit = animation::MAX_FRAMES.to_string();
+    match animation::write_apng(&mut Vec::new(), &board, &moves, &options()) {
+        Err(Error::InvalidSize(ref msg)) if msg.contains(&limit) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+    assert!(animation::replay_frames(&board, &moves, &options()).is_err());
+
+    // Every frame is limited like a rendered board
+    let board = Board::uniform(2000, 2000, 6);
+    let res = animation::write_gif(&mut Vec::new(), &board, &[], &options());
+    match res {
+        Err(Error::InvalidSize(_)) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn gif_dimensions() {
+    // 66000 pixels are too wide for a GIF, but fine for an APNG
+    let board = Board::uniform(33000, 1, 6);
+    let options = AnimationOptions {
+        render: RenderOptions {
+            cell_size: 2,
+            .. RenderOptions::default()
+        },
+        .. options()
+    };
+    let res = animation::write_gif(&mut Vec::new(), &board, &[], &options);
+    match res {
+        Err(Error::InvalidSize(ref msg)) if msg.contains("65535") => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+    assert!(animation::write_apng(&mut Vec::new(), &board, &[], &options)
+        .is_ok());
+}
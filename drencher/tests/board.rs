@@ -0,0 +1,171 @@
+extern crate drencher;
+
+use drencher::{gen_board, Board, Color, Pos};
+
+
+#[test]
+fn uniform_board_is_drenched() {
+    let board = Board::uniform(14, 14, 6);
+    assert_eq!(board.width(), 14);
+    assert_eq!(board.height(), 14);
+    assert!(board.is_drenched());
+    assert!(board.adjacent_colors().is_empty());
+}
+
+#[test]
+fn permutation_zero_is_uniform() {
+    let board = Board::permutation(3, 3, 6, 0);
+    assert!(board.is_drenched());
+    assert_eq!(board[(2, 2)], Color::new(0));
+}
+
+#[test]
+fn drench_changes_owned_field() {
+    // The second cell gets color 1, all others color 0
+    let mut board = Board::permutation(2, 2, 6, 6);
+    assert_eq!(board[(1, 0)], Color::new(1));
+    assert!(!board.is_drenched());
+    assert_eq!(board.adjacent_colors(), vec![Color::new(1)]);
+
+    board.drench(Color::new(1));
+    assert_eq!(board[(0, 0)], Color::new(1));
+    assert_eq!(board[(0, 1)], Color::new(1));
+    assert_eq!(board[(1, 1)], Color::new(1));
+    assert!(board.is_drenched());
+}
+
+#[test]
+fn deterministic_boards_are_reproducible() {
+    let a = Board::deterministic_random(10, 10, 6, 7);
+    let b = Board::deterministic_random(10, 10, 6, 7);
+    assert_eq!(a.to_string(), b.to_string());
+}
+
+#[test]
+fn gen_board_knows_all_algorithms() {
+    for algo in &["random", "deter0", "uniform", "permutations"] {
+        assert_eq!(gen_board(algo, 4, 4, 6, 0).unwrap().width(), 4);
+    }
+    assert!(gen_board("does-not-exist", 4, 4, 6, 0).is_err());
+}
+
+#[test]
+fn boards_only_use_their_colors() {
+    for colors in 1..17 {
+        let board = Board::deterministic_random(12, 12, colors, 3);
+        assert_eq!(board.colors(), colors);
+        for x in 0..12 {
+            for y in 0..12 {
+                assert!(board[(x, y)].tag < colors);
+            }
+        }
+    }
+}
+
+#[test]
+fn rectangular_boards() {
+    let mut board = Board::deterministic_random(20, 8, 6, 0);
+    assert_eq!(board.width(), 20);
+    assert_eq!(board.height(), 8);
+    assert_eq!(board.to_string().lines().count(), 8);
+
+    // the bottom right corner can be indexed
+    let _ = board[(19, 7)];
+
+    // drenching with all colors in turn eventually drenches the board
+    let mut moves = 0;
+    while !board.is_drenched() {
+        let color = board.adjacent_colors()[0];
+        board.drench(color);
+        moves += 1;
+        assert!(moves <= 160);
+    }
+}
+
+#[test]
+fn boards_larger_than_255() {
+    let mut board = Board::deterministic_random(300, 3, 6, 0);
+    assert_eq!(board.width(), 300);
+    let _ = board[(299, 2)];
+
+    board.set((299, 2), Color::new(5));
+    assert_eq!(board[(299, 2)], Color::new(5));
+    assert_eq!(board.to_string().lines().count(), 3);
+}
+
+/// Naive flood fill from the top left corner, returning the number of cells
+/// of the field and the colors adjacent to it.
+fn naive_field(board: &Board) -> (usize, Vec<Color>) {
+    let color = board[(0, 0)];
+    let width = board.width() as usize;
+    let mut visited = vec![false; width * board.height() as usize];
+    let mut stack: Vec<Pos> = vec![(0, 0)];
+    let mut size = 0;
+    let mut adjacent = Vec::new();
+
+    while let Some((x, y)) = stack.pop() {
+        let idx = y as usize * width + x as usize;
+        if visited[idx] {
+            continue;
+        }
+        if board[(x, y)] != color {
+            if !adjacent.contains(&board[(x, y)]) {
+                adjacent.push(board[(x, y)]);
+            }
+            continue;
+        }
+        visited[idx] = true;
+        size += 1;
+
+        if x > 0 {
+            stack.push((x - 1, y));
+        }
+        if y > 0 {
+            stack.push((x, y - 1));
+        }
+        if x + 1 < board.width() {
+            stack.push((x + 1, y));
+        }
+        if y + 1 < board.height() {
+            stack.push((x, y + 1));
+        }
+    }
+
+    adjacent.sort_by_key(|c| c.tag);
+    (size, adjacent)
+}
+
+#[test]
+fn incremental_field_matches_naive_flood_fill() {
+    for id in 0..10 {
+        let mut board = Board::deterministic_random(15, 9, 5, id);
+        let mut moves = 0;
+        loop {
+            let (size, adjacent) = naive_field(&board);
+            assert_eq!(board.field_size(), size);
+            assert_eq!(board.adjacent_colors(), adjacent);
+            assert_eq!(board.is_drenched(), adjacent.is_empty());
+            if board.is_drenched() {
+                break;
+            }
+
+            // cycle through the colors, including redundant moves
+            board.drench(Color::new((moves % 5) as u8));
+            moves += 1;
+        }
+    }
+}
+
+#[test]
+fn set_recalculates_field() {
+    let mut board = Board::uniform(4, 4, 3);
+    assert_eq!(board.field_size(), 16);
+
+    board.set((0, 1), Color::new(2));
+    assert_eq!(board.field_size(), 15);
+    assert_eq!(board.adjacent_colors(), vec![Color::new(2)]);
+    assert_eq!(board.border_cells(Color::new(2)), &[(0, 1)]);
+
+    board.drench(Color::new(2));
+    assert!(board.is_drenched());
+}
@@ -0,0 +1,61 @@
+extern crate drencher;
+
+use drencher::{gen_board, get_player, Error};
+
+
+fn board_error(algo: &str, width: u16, height: u16, colors: u8) -> Error {
+    match gen_board(algo, width, height, colors, 0) {
+        Ok(_) => panic!("'{}' should fail", algo),
+        Err(e) => e,
+    }
+}
+
+#[test]
+fn unknown_names() {
+    match get_player("does-not-exist") {
+        Err(Error::UnknownPlayer(ref name)) => {
+            assert_eq!(name, "does-not-exist")
+        }
+        _ => panic!("expected an unknown player error"),
+    }
+
+    let e = board_error("does-not-exist", 4, 4, 6);
+    assert_eq!(e, Error::UnknownGenerator("does-not-exist".into()));
+    assert!(e.to_string().contains("does-not-exist"));
+}
+
+#[cfg(not(feature = "gui"))]
+#[test]
+fn human_unavailable_without_gui() {
+    match get_player("human") {
+        Err(Error::PlayerUnavailable(ref name)) => assert_eq!(name, "human"),
+        _ => panic!("expected a player unavailable error"),
+    }
+    let e = Error::PlayerUnavailable("human".into());
+    assert!(e.to_string().contains("'gui' feature"));
+    assert!(get_player("terminal").is_ok());
+}
+
+#[test]
+fn invalid_size() {
+    match board_error("random", 0, 4, 6) {
+        Error::InvalidSize(_) => {}
+        e => panic!("unexpected error {:?}", e),
+    }
+    match board_error("random", 4, 4, 17) {
+        Error::InvalidSize(_) => {}
+        e => panic!("unexpected error {:?}", e),
+    }
+}
+
+#[test]
+fn parse_and_io() {
+    match board_error("id:2x2c3-0!", 4, 4, 6) {
+        Error::Parse { .. } => {}
+        e => panic!("unexpected error {:?}", e),
+    }
+    match board_error("file:/does/not/exist.txt", 4, 4, 6) {
+        Error::Io { ref path, .. } => assert_eq!(path, "/does/not/exist.txt"),
+        e => panic!("unexpected error {:?}", e),
+    }
+}
@@ -0,0 +1,98 @@
+extern crate drencher;
+
+use drencher::{Board, Color, RegionGraph};
+use drencher::graph::ROOT;
+
+
+fn board_from_rows(colors: u8, rows: &[&[u8]]) -> Board {
+    let cells = rows.iter()
+        .flat_map(|row| row.iter().map(|&tag| Color::new(tag)))
+        .collect();
+    Board::from_cells(rows[0].len() as u16, rows.len() as u16, colors, cells)
+}
+
+#[test]
+fn regions_of_small_board() {
+    let board = board_from_rows(3, &[
+        &[0, 0, 1],
+        &[2, 0, 1],
+        &[2, 2, 0],
+    ]);
+    let g = RegionGraph::new(&board);
+
+    assert_eq!(g.len(), 4);
+    assert_eq!(g.color(ROOT), Color::new(0));
+    assert_eq!(g.size(ROOT), 3);
+    assert_eq!(g.region_of((1, 1)), ROOT);
+
+    let right = g.region_of((2, 0));
+    let left = g.region_of((0, 2));
+    let corner = g.region_of((2, 2));
+    assert_eq!(g.size(right), 2);
+    assert_eq!(g.size(left), 3);
+    assert_eq!(g.size(corner), 1);
+    assert_eq!(g.neighbors(ROOT), &[right, left]);
+    assert_eq!(g.neighbors(corner), &[right, left]);
+
+    let total: usize = g.regions().map(|id| g.size(id)).sum();
+    assert_eq!(total, 9);
+}
+
+#[test]
+fn drench_merges_regions() {
+    let board = board_from_rows(3, &[
+        &[0, 0, 1],
+        &[2, 0, 1],
+        &[2, 2, 0],
+    ]);
+    let mut g = RegionGraph::new(&board);
+    let corner = g.region_of((2, 2));
+
+    assert_eq!(g.drench(Color::new(1)), 1);
+    assert_eq!(g.len(), 3);
+    assert_eq!(g.size(ROOT), 5);
+    assert_eq!(g.color(ROOT), Color::new(1));
+    assert_eq!(g.region_of((2, 1)), ROOT);
+
+    assert_eq!(g.drench(Color::new(2)), 1);
+    assert_eq!(g.neighbors(ROOT), &[corner]);
+    assert_eq!(g.drench(Color::new(0)), 1);
+    assert!(g.is_drenched());
+    assert_eq!(g.size(ROOT), 9);
+}
+
+#[test]
+fn graph_follows_board_drenching() {
+    for id in 0..10 {
+        let mut board = Board::deterministic_random(12, 7, 4, id);
+        let mut g = RegionGraph::new(&board);
+
+        while !board.is_drenched() {
+            let color = board.adjacent_colors()[0];
+            board.drench(color);
+            g.drench(color);
+
+            assert_eq!(g.size(ROOT), board.field_size());
+            assert_eq!(
+                RegionGraph::new(&board).neighbors(ROOT).len(),
+                g.neighbors(ROOT).len()
+            );
+        }
+        assert!(g.is_drenched());
+    }
+}
+
+#[test]
+fn eccentricity() {
+    // A stripe pattern: the root is at one end of a path of regions
+    let board = board_from_rows(2, &[
+        &[0, 1, 0, 1, 0],
+        &[0, 1, 0, 1, 0],
+    ]);
+    let g = RegionGraph::new(&board);
+    assert_eq!(g.eccentricity(ROOT), 4);
+    assert_eq!(g.eccentricity(g.region_of((2, 0))), 2);
+
+    let uniform = RegionGraph::new(&Board::uniform(4, 4, 6));
+    assert_eq!(uniform.eccentricity(ROOT), 0);
+}
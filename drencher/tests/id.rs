@@ -0,0 +1,94 @@
+extern crate drencher;
+
+use drencher::{Board, Color};
+
+
+#[test]
+fn id_round_trip() {
+    for &(width, height, colors) in &[(14, 14, 6), (20, 8, 3), (5, 9, 16),
+                                      (1, 1, 6), (3, 3, 1), (30, 30, 7),
+                                      (300, 200, 6), (250, 250, 16)] {
+        let board = Board::deterministic_random(width, height, colors, 5);
+        let id = board.to_id();
+        let parsed = Board::from_id(&id).unwrap();
+        assert_eq!(parsed.to_text(), board.to_text(), "id: {}", id);
+        assert_eq!(parsed.to_id(), id);
+    }
+}
+
+#[test]
+fn ids_match_permutations() {
+    // every possible 2x2 board with 3 colors
+    let mut ids = Vec::new();
+    for n in 0..81 {
+        let board = Board::permutation(2, 2, 3, n);
+        let id = board.to_id();
+        assert_eq!(Board::from_id(&id).unwrap().to_text(), board.to_text());
+        ids.push(id);
+    }
+
+    // all IDs are different and have the same length
+    let len = ids[0].len();
+    assert!(ids.iter().all(|id| id.len() == len));
+    ids.sort();
+    ids.dedup();
+    assert_eq!(ids.len(), 81);
+}
+
+#[test]
+fn simple_ids() {
+    assert_eq!(Board::uniform(2, 2, 3).to_id(), "2x2c3-00");
+
+    // top left cell is the least significant digit
+    let board = Board::from_id("2x1c2-1").unwrap();
+    assert_eq!(board[(0, 0)], Color::new(1));
+    assert_eq!(board[(1, 0)], Color::new(0));
+
+    // the example from the module documentation
+    let board = Board::deterministic_random(4, 3, 6, 2);
+    assert_eq!(board.to_id(), "4x3c6-1bWgJi");
+}
+
+#[test]
+fn id_lengths() {
+    // ceil(cells * log2(colors) / 6) characters after the '-'
+    let payload_len = |w, h, c| {
+        let id = Board::uniform(w, h, c).to_id();
+        id.len() - id.find('-').unwrap() - 1
+    };
+    assert_eq!(payload_len(4, 3, 6), 6);
+    assert_eq!(payload_len(3, 2, 4), 2);
+    assert_eq!(payload_len(7, 1, 2), 2);
+    assert_eq!(payload_len(100, 100, 6), 4309);
+    assert_eq!(payload_len(5, 5, 1), 1);
+}
+
+#[test]
+fn invalid_ids() {
+    // 3^4 - 1 = 80 = "1G" in base 64, so "1H" is too big
+    assert!(Board::from_id("2x2c3-1G").is_ok());
+    assert!(Board::from_id("2x2c3-1H").is_err());
+
+    // wrong length
+    assert!(Board::from_id("2x2c3-0").is_err());
+    assert!(Board::from_id("2x2c3-000").is_err());
+
+    // broken headers
+    assert!(Board::from_id("2x2-00").is_err());
+    assert!(Board::from_id("2c3-00").is_err());
+    assert!(Board::from_id("0x2c3-00").is_err());
+    assert!(Board::from_id("2x2c17-00").is_err());
+    assert!(Board::from_id("2x2c3").is_err());
+    assert!(Board::from_id("2x2c3-0!").is_err());
+
+    // every number in the header has only one representation
+    assert!(Board::from_id("02x2c3-00").is_err());
+    assert!(Board::from_id("2x+2c3-00").is_err());
+    assert!(Board::from_id("2x2c03-00").is_err());
+    assert!(Board::from_id("2x2c+3-00").is_err());
+    assert!(Board::from_id("2x2c-00").is_err());
+
+    // only the uniform board exists with one color
+    assert!(Board::from_id("2x2c1-0").is_ok());
+    assert!(Board::from_id("2x2c1-1").is_err());
+}
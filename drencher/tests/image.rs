@@ -0,0 +1,196 @@
+extern crate drencher;
+extern crate png;
+
+use drencher::Board;
+use drencher::image::{self, Image, ImportOptions, Rgb};
+use png::HasParameters;
+
+
+/// Draws the board with `cell` pixels per cell and black grid lines between
+/// the cells, like a screenshot would look.
+fn draw(board: &Board, palette: &[Rgb], cell: u32) -> Image {
+    let width = board.width() as u32 * cell;
+    let height = board.height() as u32 * cell;
+    let mut pixels = Vec::new();
+    for y in 0..height {
+        for x in 0..width {
+            if x % cell == 0 || y % cell == 0 {
+                pixels.push([0, 0, 0]);
+            } else {
+                let pos = ((x / cell) as u16, (y / cell) as u16);
+                pixels.push(palette[board[pos].tag as usize]);
+            }
+        }
+    }
+    Image::new(width, height, pixels)
+}
+
+/// Encodes the image as binary PPM.
+fn to_ppm(img: &Image) -> Vec<u8> {
+    let mut out = format!("P6\n# test\n{} {}\n255\n", img.width(), img.height())
+        .into_bytes();
+    for y in 0..img.height() {
+        for x in 0..img.width() {
+            out.extend_from_slice(&img.pixel(x, y));
+        }
+    }
+    out
+}
+
+/// Encodes the image as RGB PNG with the given bit depth (8 or 16).
+fn to_png(img: &Image, depth: png::BitDepth) -> Vec<u8> {
+    let mut data = Vec::new();
+    for y in 0..img.height() {
+        for x in 0..img.width() {
+            for &v in &img.pixel(x, y) {
+                data.push(v);
+                if depth == png::BitDepth::Sixteen {
+                    data.push(v);
+                }
+            }
+        }
+    }
+
+    let mut out = Vec::new();
+    {
+        let (width, height) = (img.width(), img.height());
+        let mut encoder = png::Encoder::new(&mut out, width, height);
+        encoder.set(png::ColorType::RGB).set(depth);
+        let mut writer = encoder.write_header().unwrap();
+        writer.write_image_data(&data).unwrap();
+    }
+    out
+}
+
+#[test]
+fn import_default_palette() {
+    let board = Board::deterministic_random(9, 7, 6, 3);
+    let img = draw(&board, &image::default_palette(6), 10);
+
+    let import = image::import_board(&img, &ImportOptions::new(9, 7, 6))
+        .unwrap();
+    assert_eq!(import.board.to_text(), board.to_text());
+    assert!(import.ambiguous.is_empty());
+}
+
+#[test]
+fn import_custom_palette() {
+    let palette = image::parse_palette("#ff0000,00ff00,0000ff,ffffff").unwrap();
+    let board = Board::deterministic_random(5, 5, 4, 8);
+    let img = draw(&board, &palette, 6);
+
+    let mut options = ImportOptions::new(5, 5, 4);
+    options.palette = palette;
+    let import = image::import_board(&img, &options).unwrap();
+    assert_eq!(import.board.to_text(), board.to_text());
+    assert_eq!(import.board.colors(), 4);
+}
+
+#[test]
+fn import_area() {
+    // The board is surrounded by a white frame of 5 pixels
+    let board = Board::deterministic_random(4, 3, 6, 1);
+    let inner = draw(&board, &image::default_palette(6), 8);
+    let (width, height) = (inner.width() + 10, inner.height() + 10);
+    let mut pixels = Vec::new();
+    for y in 0..height {
+        for x in 0..width {
+            if x < 5 || y < 5 || x >= width - 5 || y >= height - 5 {
+                pixels.push([255, 255, 255]);
+            } else {
+                pixels.push(inner.pixel(x - 5, y - 5));
+            }
+        }
+    }
+    let img = Image::new(width, height, pixels);
+
+    let mut options = ImportOptions::new(4, 3, 6);
+    options.area = Some((5, 5, inner.width(), inner.height()));
+    let import = image::import_board(&img, &options).unwrap();
+    assert_eq!(import.board.to_text(), board.to_text());
+}
+
+#[test]
+fn ambiguous_cells() {
+    let palette = vec![[0, 0, 0], [200, 200, 200]];
+    let board = Board::uniform(3, 2, 2);
+    let mut img = draw(&board, &palette, 4);
+
+    // Cell (1, 1) gets a color right between both palette entries
+    let mut pixels = Vec::new();
+    for y in 0..img.height() {
+        for x in 0..img.width() {
+            if x / 4 == 1 && y / 4 == 1 {
+                pixels.push([100, 100, 101]);
+            } else {
+                pixels.push(img.pixel(x, y));
+            }
+        }
+    }
+    img = Image::new(img.width(), img.height(), pixels);
+
+    let mut options = ImportOptions::new(3, 2, 2);
+    options.palette = palette;
+    let import = image::import_board(&img, &options).unwrap();
+    assert_eq!(import.ambiguous, vec![(1, 1)]);
+}
+
+#[test]
+fn read_ppm() {
+    let board = Board::deterministic_random(6, 4, 6, 2);
+    let img = draw(&board, &image::default_palette(6), 5);
+    let read = Image::read_ppm(&to_ppm(&img)[..]).unwrap();
+    assert_eq!((read.width(), read.height()), (img.width(), img.height()));
+    assert_eq!(read.pixel(7, 3), img.pixel(7, 3));
+
+    let ascii = "P3 # comment\n2 1\n15\n15 0 0  0 0 15\n";
+    let read = Image::read_ppm(ascii.as_bytes()).unwrap();
+    assert_eq!(read.pixel(0, 0), [255, 0, 0]);
+    assert_eq!(read.pixel(1, 0), [0, 0, 255]);
+}
+
+#[test]
+fn read_png() {
+    let board = Board::deterministic_random(6, 4, 6, 2);
+    let img = draw(&board, &image::default_palette(6), 5);
+
+    // 16 bit values are stripped to their high byte
+    for &depth in &[png::BitDepth::Eight, png::BitDepth::Sixteen] {
+        let read = Image::read_png(&to_png(&img, depth)[..]).unwrap();
+        assert_eq!((read.width(), read.height()), (img.width(), img.height()));
+        for y in 0..img.height() {
+            for x in 0..img.width() {
+                assert_eq!(read.pixel(x, y), img.pixel(x, y));
+            }
+        }
+
+        let import = image::import_board(&read, &ImportOptions::new(6, 4, 6))
+            .unwrap();
+        assert_eq!(import.board.to_text(), board.to_text());
+    }
+}
+
+#[test]
+fn invalid_input() {
+    assert!(Image::read_ppm(&b"P6\n2 2\n255\n\x00"[..]).is_err());
+    assert!(Image::read_ppm(&b"P5\n1 1\n255\n\x00"[..]).is_err());
+
+    // huge sizes in the header are rejected before allocating the pixels
+    assert!(Image::read_ppm(&b"P6 65535 65535 255\n\x00"[..]).is_err());
+    assert!(Image::read_ppm(&b"P3 65535 65535 255\n0 0 0"[..]).is_err());
+    let overflow = "P6 4294967295 4294967295 255\n";
+    assert!(Image::read_ppm(overflow.as_bytes()).is_err());
+
+    assert!(Image::read_png(&b"\x89PNG\r\n\x1a\n"[..]).is_err());
+    assert!(image::parse_palette("ff0000,12345").is_err());
+
+    let img = Image::new(2, 2, vec![[0, 0, 0]; 4]);
+    assert!(image::import_board(&img, &ImportOptions::new(3, 3, 6)).is_err());
+
+    // the area has to be inside the image, even if its end overflows
+    let mut options = ImportOptions::new(1, 1, 6);
+    options.area = Some((1, 1, 2, 1));
+    assert!(image::import_board(&img, &options).is_err());
+    options.area = Some((1, 0, ::std::u32::MAX, 1));
+    assert!(image::import_board(&img, &options).is_err());
+}
@@ -0,0 +1,67 @@
+extern crate drencher;
+extern crate rustc_serialize;
+
+use drencher::{Board, Color};
+use drencher::json::{self, FromJson, GameRecord};
+use rustc_serialize::json::ToJson;
+use std::slice;
+
+
+#[test]
+fn board_round_trip() {
+    for &(width, height, colors) in &[(14, 14, 6), (20, 8, 3), (5, 9, 16)] {
+        let board = Board::deterministic_random(width, height, colors, 1);
+        let s = board.to_json().to_string();
+        let parsed = Board::from_json_str(&s).unwrap();
+        assert_eq!(parsed.to_text(), board.to_text());
+    }
+}
+
+#[test]
+fn solution_round_trip() {
+    let moves = vec![Color::new(0), Color::new(5), Color::new(2)];
+    let json = json::solution_to_json(&moves);
+    assert_eq!(json.to_string(), r#"{"moves":[0,5,2],"version":1}"#);
+    assert_eq!(json::solution_from_json(&json, 6).unwrap(), moves);
+
+    // color 5 doesn't exist with only 3 colors
+    assert!(json::solution_from_json(&json, 3).is_err());
+}
+
+#[test]
+fn game_record_round_trip() {
+    let record = GameRecord {
+        board: Board::deterministic_random(6, 4, 6, 9),
+        generator: "deter0".into(),
+        seed: Some(9),
+        player: "heuristic".into(),
+        moves: vec![Color::new(1), Color::new(3)],
+        solved: false,
+    };
+
+    let json = json::games_to_json(slice::from_ref(&record));
+    let s = json::to_pretty_string(&json);
+    let games = json::games_from_json(&json::parse(&s).unwrap()).unwrap();
+    assert_eq!(games.len(), 1);
+    let parsed = &games[0];
+    assert_eq!(parsed.board.to_text(), record.board.to_text());
+    assert_eq!(parsed.generator, "deter0");
+    assert_eq!(parsed.seed, Some(9));
+    assert_eq!(parsed.player, "heuristic");
+    assert_eq!(parsed.moves, record.moves);
+    assert!(!parsed.solved);
+}
+
+#[test]
+fn reject_newer_versions_and_bad_boards() {
+    let newer = r#"{"version":2,"width":1,"height":1,"colors":6,"rows":["0"]}"#;
+    assert!(Board::from_json_str(newer).is_err());
+
+    let bad_row = r#"{"version":1,"width":2,"height":1,"colors":6,"rows":["0"]}"#;
+    assert!(Board::from_json_str(bad_row).is_err());
+
+    let bad_cell = r#"{"version":1,"width":1,"height":1,"colors":2,"rows":["5"]}"#;
+    assert!(Board::from_json_str(bad_cell).is_err());
+
+    assert!(Board::from_json_str("{").is_err());
+}
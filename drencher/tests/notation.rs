@@ -0,0 +1,44 @@
+extern crate drencher;
+
+use drencher::Color;
+use drencher::color::MAX_COLORS;
+use drencher::notation::{format_moves, parse_moves};
+
+
+#[test]
+fn format() {
+    let moves = vec![Color::new(2), Color::new(0), Color::new(11),
+                     Color::new(15)];
+    assert_eq!(format_moves(&moves), "20bf");
+    assert_eq!(format_moves(&[]), "");
+}
+
+#[test]
+fn round_trip_all_color_counts() {
+    for colors in 1..MAX_COLORS + 1 {
+        let moves: Vec<_> = (0..50)
+            .map(|i| Color::new(((i * 7 + 3) % colors as usize) as u8))
+            .collect();
+        let s = format_moves(&moves);
+        assert_eq!(s.len(), moves.len());
+        assert_eq!(parse_moves(&s, colors).unwrap(), moves);
+    }
+}
+
+#[test]
+fn parse_case_and_whitespace() {
+    let moves = parse_moves(" 01 2A\tbC\n", 16).unwrap();
+    let tags: Vec<_> = moves.iter().map(|c| c.tag).collect();
+    assert_eq!(tags, vec![0, 1, 2, 10, 11, 12]);
+}
+
+#[test]
+fn parse_errors() {
+    let e = parse_moves("0126", 6).unwrap_err();
+    assert_eq!(e.position, 4);
+
+    let e = parse_moves("01,2", 6).unwrap_err();
+    assert_eq!(e.position, 3);
+
+    assert!(parse_moves("x", 16).is_err());
+}
@@ -0,0 +1,150 @@
+extern crate drencher;
+
+use drencher::{Board, Color, Error};
+use drencher::image::{self, Image};
+use drencher::render::{self, RenderOptions};
+
+
+fn plain() -> RenderOptions {
+    RenderOptions {
+        cell_size: 4,
+        grid: false,
+        coordinates: false,
+        highlight_owned: false,
+    }
+}
+
+#[test]
+fn board_cells() {
+    let board = Board::deterministic_random(5, 3, 6, 4);
+    let img = render::render_board(&board, &plain()).unwrap();
+    assert_eq!((img.width(), img.height()), (20, 12));
+
+    for y in 0..board.height() {
+        for x in 0..board.width() {
+            let expected = image::to_rgb(board[(x, y)]);
+            let (px, py) = (x as u32 * 4, y as u32 * 4);
+            assert_eq!(img.pixel(px, py), expected);
+            assert_eq!(img.pixel(px + 3, py + 3), expected);
+        }
+    }
+}
+
+#[test]
+fn solution_strip() {
+    let board = Board::deterministic_random(4, 4, 3, 2);
+    let moves = vec![Color::new(1), Color::new(2), Color::new(0)];
+    let img = render::render_solution(&board, &moves, &plain()).unwrap();
+
+    // 4 boards of 16 pixels with 3 gaps of one cell
+    assert_eq!((img.width(), img.height()), (4 * 16 + 3 * 4, 16));
+
+    let mut state = board.clone();
+    for (i, &m) in moves.iter().enumerate() {
+        state.drench(m);
+        let left = (i as u32 + 1) * 20;
+        assert_eq!(img.pixel(left, 0), image::to_rgb(state[(0, 0)]));
+        assert_eq!(img.pixel(left + 15, 15), image::to_rgb(state[(3, 3)]));
+    }
+}
+
+#[test]
+fn grid_coordinates_and_highlight() {
+    let board = Board::deterministic_random(12, 12, 6, 1);
+    let options = RenderOptions {
+        cell_size: 16,
+        grid: true,
+        coordinates: true,
+        highlight_owned: true,
+    };
+    let img = render::render_board(&board, &options).unwrap();
+    let plain = render::render_board(&board, &RenderOptions {
+        cell_size: 16,
+        .. plain()
+    }).unwrap();
+
+    // The labels need space on the left and on top
+    assert!(img.width() > plain.width());
+    assert!(img.height() > plain.height());
+    let left = img.width() - plain.width();
+    let top = img.height() - plain.height();
+
+    // The owned top left cell has an outline, the middle of it doesn't.
+    assert_eq!(img.pixel(left, top), [0, 0, 0]);
+    assert_eq!(img.pixel(left + 8, top + 8), image::to_rgb(board[(0, 0)]));
+
+    // A cell far away from the field only has the grid lines
+    let (px, py) = (left + 11 * 16, top + 11 * 16);
+    assert_eq!(img.pixel(px + 8, py + 8), image::to_rgb(board[(11, 11)]));
+    assert!(img.pixel(px, py + 8) != image::to_rgb(board[(11, 11)]));
+}
+
+#[test]
+fn svg() {
+    let board = Board::deterministic_random(3, 2, 6, 9);
+    let svg = render::board_to_svg(&board, &plain()).unwrap();
+    assert!(svg.starts_with("<svg"));
+    assert!(svg.trim_end().ends_with("</svg>"));
+    assert!(svg.contains("width=\"12\" height=\"8\""));
+
+    // background and one rectangle per cell
+    assert_eq!(svg.matches("<rect").count(), 1 + 6);
+    let c = image::to_rgb(board[(2, 1)]);
+    let fill = format!("#{:02x}{:02x}{:02x}", c[0], c[1], c[2]);
+    assert!(svg.contains(&fill));
+
+    let options = RenderOptions {
+        coordinates: true,
+        .. plain()
+    };
+    let moves = vec![Color::new(3)];
+    let svg = render::solution_to_svg(&board, &moves, &options).unwrap();
+    assert!(svg.contains("<text"));
+}
+
+#[test]
+fn png_round_trip() {
+    let board = Board::deterministic_random(7, 5, 6, 3);
+    let img = render::render_board(&board, &RenderOptions::default()).unwrap();
+
+    let mut png = Vec::new();
+    img.write_png(&mut png).unwrap();
+    let read = Image::read_png(&png[..]).unwrap();
+    assert_eq!((read.width(), read.height()), (img.width(), img.height()));
+    for &(x, y) in &[(0, 0), (8, 8), (50, 30), (111, 79)] {
+        assert_eq!(read.pixel(x, y), img.pixel(x, y));
+    }
+}
+
+#[test]
+fn unknown_extension() {
+    let board = Board::uniform(2, 2, 6);
+    let res = render::save_board("board.jpg", &board, &plain());
+    assert!(res.is_err());
+}
+
+#[test]
+fn too_big() {
+    // 2000x2000 cells of 16 pixels would need 3 GB
+    let board = Board::uniform(2000, 2000, 6);
+    match render::render_board(&board, &RenderOptions::default()) {
+        Err(Error::InvalidSize(msg)) => {
+            assert!(msg.contains(&render::MAX_PIXELS.to_string()));
+        }
+        other => panic!("unexpected result: {:?}", other.map(|_| ())),
+    }
+
+    // The same board fits with one pixel per cell
+    let options = RenderOptions {
+        cell_size: 1,
+        .. plain()
+    };
+    assert!(render::render_board(&board, &options).is_ok());
+
+    // A long strip of small boards
+    let board = Board::uniform(10, 10, 6);
+    let moves = vec![Color::new(1); 100_000];
+    assert!(render::render_solution(&board, &moves, &plain()).is_err());
+    assert!(render::save_solution("strip.png", &board, &moves, &plain())
+        .is_err());
+}
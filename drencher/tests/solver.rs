@@ -0,0 +1,298 @@
+extern crate drencher;
+extern crate time;
+
+use drencher::{get_player, Board, Color, Solution, Solver};
+use drencher::solver::{CancelToken, FailureReason, IterativeDeepening};
+use drencher::solver::Progress;
+use time::Duration;
+
+
+fn check_solution(board: &Board, moves: &Solution) -> bool {
+    let mut board = board.clone();
+    for &c in moves {
+        board.drench(c);
+    }
+    board.is_drenched()
+}
+
+#[test]
+fn automatic_players_solve_boards() {
+    let names = [
+        "exact", "astar", "iddfs", "bnb", "heuristic", "modcount", "random",
+    ];
+    for name in &names {
+        let player = get_player(name).unwrap();
+        for id in 0..5 {
+            let board = Board::deterministic_random(6, 6, 6, id);
+            // the random player is allowed to fail
+            let outcome = player.solve(board.clone());
+            if outcome.is_solved() {
+                assert!(
+                    check_solution(&board, &outcome.moves),
+                    "{} failed",
+                    name,
+                );
+            } else {
+                assert_eq!(*name, "random");
+                assert_eq!(outcome.failure, Some(FailureReason::MoveLimit));
+            }
+        }
+    }
+}
+
+#[test]
+fn exact_is_not_worse_than_heuristic() {
+    let exact = get_player("exact").unwrap();
+    let heuristic = get_player("heuristic").unwrap();
+    for id in 0..10 {
+        let board = Board::deterministic_random(8, 8, 6, id);
+        let optimal = exact.solve(board.clone()).moves;
+        let approx = heuristic.solve(board.clone()).moves;
+        assert!(check_solution(&board, &optimal));
+        assert!(optimal.len() <= approx.len());
+    }
+}
+
+#[test]
+fn unknown_player_is_an_error() {
+    assert!(get_player("does-not-exist").is_err());
+}
+
+#[test]
+fn solvers_handle_different_color_counts() {
+    for &colors in &[3, 4, 9, 16] {
+        for name in &["exact", "heuristic", "modcount"] {
+            let player = get_player(name).unwrap();
+            let board = Board::deterministic_random(5, 5, colors, 1);
+            let moves = player.solve(board.clone()).moves;
+            assert!(check_solution(&board, &moves), "{} failed", name);
+            assert!(moves.iter().all(|c| c.tag < colors));
+        }
+    }
+}
+
+#[test]
+fn solvers_handle_rectangular_boards() {
+    for &(width, height) in &[(20, 8), (3, 11), (1, 9), (12, 1)] {
+        for name in &["exact", "heuristic", "modcount"] {
+            let player = get_player(name).unwrap();
+            let board = Board::deterministic_random(width, height, 6, 2);
+            let moves = player.solve(board.clone()).moves;
+            assert!(check_solution(&board, &moves), "{} failed", name);
+        }
+    }
+}
+
+fn checkerboard(width: u16, height: u16) -> Board {
+    let mut cells = Vec::new();
+    for y in 0..height {
+        for x in 0..width {
+            cells.push(Color::new(((x + y) % 2) as u8));
+        }
+    }
+    Board::from_cells(width, height, 2, cells)
+}
+
+#[test]
+fn exact_handles_many_islands() {
+    // Every cell is an island, so the graph has 400 nodes.
+    let board = checkerboard(20, 20);
+    let moves = get_player("exact").unwrap().solve(board.clone()).moves;
+    assert!(check_solution(&board, &moves));
+    assert_eq!(moves.len(), 38);
+}
+
+#[test]
+fn astar_matches_exact() {
+    let exact = get_player("exact").unwrap();
+    let astar = get_player("astar").unwrap();
+    let boards = (0..10).map(|id| Board::deterministic_random(8, 8, 6, id))
+        .chain((0..3).map(|id| Board::deterministic_random(10, 10, 4, id)))
+        .chain(Some(checkerboard(20, 20)));
+
+    for board in boards {
+        let expected = exact.solve(board.clone());
+        let outcome = astar.solve(board.clone());
+        assert!(outcome.is_solved());
+        assert!(outcome.optimal);
+        assert!(check_solution(&board, &outcome.moves));
+        assert_eq!(outcome.moves.len(), expected.moves.len());
+    }
+}
+
+// Compares the nodes expanded by A* and the exact solver on bigger boards.
+// Takes about 20 seconds in a release build:
+// `cargo test --release -- --ignored astar_expands_fewer_nodes`
+#[test]
+#[ignore]
+fn astar_expands_fewer_nodes_than_exact() {
+    let exact = get_player("exact").unwrap();
+    let astar = get_player("astar").unwrap();
+
+    for id in 0..3 {
+        let board = Board::deterministic_random(14, 14, 6, id);
+        let expected = exact.solve(board.clone());
+        let outcome = astar.solve(board.clone());
+        assert!(outcome.is_solved() && outcome.optimal);
+        assert!(check_solution(&board, &outcome.moves));
+        assert_eq!(outcome.moves.len(), expected.moves.len());
+        assert!(
+            outcome.nodes_expanded < expected.nodes_expanded,
+            "board {}: A* expanded {} nodes, exact {}",
+            id,
+            outcome.nodes_expanded,
+            expected.nodes_expanded,
+        );
+    }
+}
+
+#[test]
+fn iterative_deepening_matches_exact() {
+    let exact = get_player("exact").unwrap();
+    let iddfs = get_player("iddfs").unwrap();
+    // a tiny transposition table must not change the result
+    let tiny = IterativeDeepening::with_table_size(1024);
+
+    for id in 0..8 {
+        let board = Board::deterministic_random(8, 8, 6, id);
+        let expected = exact.solve(board.clone()).moves.len();
+        let outcomes = vec![
+            iddfs.solve(board.clone()),
+            tiny.solve(board.clone()),
+        ];
+        for outcome in outcomes {
+            assert!(outcome.is_solved());
+            assert!(outcome.optimal);
+            assert!(check_solution(&board, &outcome.moves));
+            assert_eq!(outcome.moves.len(), expected);
+        }
+    }
+}
+
+#[test]
+fn branch_and_bound_improves_heuristic() {
+    let exact = get_player("exact").unwrap();
+    let heuristic = get_player("heuristic").unwrap();
+    let bnb = get_player("bnb").unwrap();
+
+    for id in 0..8 {
+        let board = Board::deterministic_random(8, 8, 6, id);
+        let expected = exact.solve(board.clone()).moves.len();
+        let initial = heuristic.solve(board.clone()).moves.len();
+
+        let outcome = bnb.solve(board.clone());
+        assert!(outcome.is_solved());
+        assert!(outcome.optimal);
+        assert!(check_solution(&board, &outcome.moves));
+        assert_eq!(outcome.moves.len(), expected);
+        assert_eq!(outcome.initial_bound, Some(initial));
+    }
+
+    // cancelled right after the heuristic, its solution is returned
+    let board = Board::deterministic_random(14, 14, 6, 0);
+    let cancel = CancelToken::new();
+    let outcome = bnb.solve_with_progress(
+        board.clone(),
+        &cancel,
+        &mut |_| cancel.cancel(),
+    );
+    assert_eq!(outcome.failure, Some(FailureReason::Aborted));
+    assert!(!outcome.optimal);
+    assert!(check_solution(&board, &outcome.moves));
+    assert_eq!(outcome.initial_bound, Some(outcome.moves.len()));
+}
+
+#[test]
+fn exact_rejects_too_many_islands() {
+    let board = checkerboard(70, 70);
+    for name in &["exact", "astar", "iddfs", "bnb"] {
+        let outcome = get_player(name).unwrap().solve(board.clone());
+        assert_eq!(outcome.failure, Some(FailureReason::Unsupported));
+    }
+}
+
+#[test]
+fn heuristic_handles_wide_boards() {
+    let board = Board::deterministic_random(400, 2, 6, 4);
+    let moves = get_player("heuristic").unwrap().solve(board.clone()).moves;
+    assert!(check_solution(&board, &moves));
+}
+
+#[test]
+fn exact_reports_optimality_and_statistics() {
+    let board = Board::deterministic_random(8, 8, 6, 3);
+    let outcome = get_player("exact").unwrap().solve(board.clone());
+    assert!(outcome.is_solved());
+    assert!(outcome.optimal);
+    assert!(outcome.nodes_expanded > 0);
+    assert!(outcome.peak_frontier > 0);
+
+    let outcome = get_player("heuristic").unwrap().solve(board);
+    assert!(outcome.is_solved());
+    assert!(!outcome.optimal);
+    assert_eq!(outcome.nodes_expanded, 0);
+}
+
+#[test]
+fn cancelled_solvers_abort() {
+    let cancel = CancelToken::new();
+    cancel.clone().cancel();
+    assert!(cancel.is_cancelled());
+
+    let board = Board::deterministic_random(14, 14, 6, 0);
+    for name in &["exact", "astar", "iddfs", "bnb", "heuristic", "random",
+                  "modcount"] {
+        let outcome = get_player(name).unwrap()
+            .solve_until(board.clone(), &cancel);
+        assert_eq!(outcome.failure, Some(FailureReason::Aborted), "{}", name);
+        assert!(!outcome.optimal);
+    }
+}
+
+#[test]
+fn cancel_tokens_with_timeout() {
+    assert!(CancelToken::with_timeout(Duration::zero()).is_cancelled());
+    assert!(!CancelToken::with_timeout(Duration::hours(1)).is_cancelled());
+}
+
+#[test]
+fn exact_returns_partial_solution_when_cancelled() {
+    // Cancel the search once the first level of the game tree is done
+    let board = Board::deterministic_random(14, 14, 6, 0);
+    let cancel = CancelToken::new();
+    let outcome = get_player("exact").unwrap().solve_with_progress(
+        board,
+        &cancel,
+        &mut |p: &Progress| if p.depth >= 1 { cancel.cancel() },
+    );
+    assert_eq!(outcome.failure, Some(FailureReason::Aborted));
+    assert!(!outcome.moves.is_empty());
+}
+
+#[test]
+fn exact_reports_progress() {
+    let board = Board::deterministic_random(8, 8, 6, 5);
+    let mut reports = Vec::new();
+    let outcome = get_player("exact").unwrap().solve_with_progress(
+        board.clone(),
+        &CancelToken::new(),
+        &mut |p: &Progress| reports.push(*p),
+    );
+
+    // one report per level of the game tree, except the last one
+    assert_eq!(reports.len(), outcome.moves.len());
+    for (i, report) in reports.iter().enumerate() {
+        assert_eq!(report.depth, i);
+        assert!(report.frontier > 0);
+        assert!(report.frontier <= outcome.peak_frontier);
+    }
+    assert!(reports.windows(2).all(|w| w[0].pruned <= w[1].pruned));
+
+    let mut called = false;
+    get_player("heuristic").unwrap().solve_with_progress(
+        board,
+        &CancelToken::new(),
+        &mut |_| called = true,
+    );
+    assert!(!called);
+}
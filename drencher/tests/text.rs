@@ -0,0 +1,59 @@
+extern crate drencher;
+
+use drencher::{Board, Color, ParseBoardError};
+
+
+#[test]
+fn parse_simple_board() {
+    let board: Board = "# comment\n\n012\n345\n".parse().unwrap();
+    assert_eq!(board.width(), 3);
+    assert_eq!(board.height(), 2);
+    assert_eq!(board.colors(), 6);
+    assert_eq!(board[(2, 1)], Color::new(5));
+}
+
+#[test]
+fn parse_colors_header_and_letters() {
+    let board: Board = "colors: 16\n0aF\nb9c\n".parse().unwrap();
+    assert_eq!(board.colors(), 16);
+    assert_eq!(board[(1, 0)], Color::new(10));
+    assert_eq!(board[(2, 0)], Color::new(15));
+
+    // without header the number of colors is derived from the cells
+    let board: Board = "07\n".parse().unwrap();
+    assert_eq!(board.colors(), 8);
+}
+
+#[test]
+fn round_trip() {
+    for &(width, height, colors) in &[(14, 14, 6), (20, 8, 3), (5, 9, 16)] {
+        let board = Board::deterministic_random(width, height, colors, 3);
+        let parsed: Board = board.to_text().parse().unwrap();
+        assert_eq!(parsed.to_text(), board.to_text());
+        assert_eq!(parsed.colors(), colors);
+    }
+}
+
+#[test]
+fn errors_have_line_and_column() {
+    fn err(s: &str) -> ParseBoardError {
+        s.parse::<Board>().err().unwrap()
+    }
+
+    let e = err("012\n0x2\n");
+    assert_eq!((e.line, e.column), (2, 2));
+
+    let e = err("012\n01\n");
+    assert_eq!((e.line, e.column), (2, 3));
+
+    let e = err("colors: 3\n# comment\n  0123\n");
+    assert_eq!((e.line, e.column), (3, 6));
+
+    let e = err("colors: 99\n");
+    assert_eq!(e.line, 1);
+
+    let e = err("012\ncolors: 3\n");
+    assert_eq!((e.line, e.column), (2, 1));
+
+    assert!("# only a comment\n".parse::<Board>().is_err());
+}
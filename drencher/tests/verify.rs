@@ -0,0 +1,78 @@
+extern crate drencher;
+
+use drencher::{Board, Color};
+use drencher::verify;
+
+
+fn board_from_rows(colors: u8, rows: &[&[u8]]) -> Board {
+    let cells = rows.iter()
+        .flat_map(|row| row.iter().map(|&tag| Color::new(tag)))
+        .collect();
+    Board::from_cells(rows[0].len() as u16, rows.len() as u16, colors, cells)
+}
+
+fn moves(tags: &[u8]) -> Vec<Color> {
+    tags.iter().map(|&t| Color::new(t)).collect()
+}
+
+#[test]
+fn valid_solution() {
+    let board = board_from_rows(3, &[
+        &[0, 1, 2],
+        &[1, 1, 2],
+    ]);
+    let res = verify::verify(&board, &moves(&[1, 2]));
+
+    assert!(res.is_valid());
+    assert!(res.drenched);
+    assert_eq!(res.drenched_after, Some(2));
+    assert_eq!(res.moves[0].captured, 3);
+    assert_eq!(res.moves[0].owned, 4);
+    assert_eq!(res.moves[1].captured, 2);
+    assert!(res.moves.iter().all(|m| m.legal && !m.redundant));
+}
+
+#[test]
+fn redundant_moves() {
+    let board = board_from_rows(3, &[
+        &[0, 1, 2],
+        &[0, 1, 2],
+    ]);
+
+    // same color as the field, no capture, and a move after the end
+    let res = verify::verify(&board, &moves(&[0, 2, 1, 2, 0]));
+    let redundant: Vec<_> = res.moves.iter().map(|m| m.redundant).collect();
+    assert_eq!(redundant, vec![true, true, false, false, true]);
+    assert_eq!(res.redundant_count(), 3);
+    assert_eq!(res.drenched_after, Some(4));
+    assert!(res.is_valid());
+}
+
+#[test]
+fn illegal_moves() {
+    let board = board_from_rows(2, &[
+        &[0, 1],
+    ]);
+    let res = verify::verify(&board, &moves(&[5, 1]));
+
+    assert!(!res.moves[0].legal);
+    assert_eq!(res.moves[0].captured, 0);
+    assert!(res.moves[1].legal);
+    assert!(res.drenched);
+    assert_eq!(res.illegal_count(), 1);
+    assert!(!res.is_valid());
+}
+
+#[test]
+fn not_drenched() {
+    let board = Board::deterministic_random(10, 10, 6, 3);
+    let res = verify::verify(&board, &moves(&[1, 2, 3]));
+    assert!(!res.drenched);
+    assert_eq!(res.drenched_after, None);
+    assert!(!res.is_valid());
+
+    // no moves at all are only valid on a drenched board
+    assert!(!verify::verify(&board, &[]).is_valid());
+    let uniform = Board::uniform(3, 3, 6);
+    assert_eq!(verify::verify(&uniform, &[]).drenched_after, Some(0));
+}